@@ -0,0 +1,189 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use clap::{ArgAction, Args, Parser, Subcommand};
+use tracing::Level;
+use wikibase::{DatasetKind, ErrorKind};
+
+
+#[derive(Debug, Parser)]
+#[command(version, about = "Collects seldomly changing country data from Wikipedia and stores it as JSON")]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+    /// Map of ISO 3166 codes to country names used to identify countries
+    #[arg(short, long, global = true, default_value = "input/countries.json")]
+    pub input: PathBuf,
+    /// Directory results are read from and written to
+    #[arg(short, long, global = true, default_value = "output")]
+    pub output: PathBuf,
+    /// Record every fetched document to this directory
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+    /// Read documents recorded with --record instead of fetching them
+    #[arg(long, global = true, value_name = "DIR")]
+    pub replay: Option<PathBuf>,
+    /// User-Agent sent with every request, Wikimedia asks for a way to contact the operator
+    #[arg(long, global = true, value_name = "AGENT")]
+    pub user_agent: Option<String>,
+    /// How many times failed requests are retried, with exponential backoff
+    #[arg(long, global = true, value_name = "N", default_value_t = 4)]
+    pub retries: u32,
+    /// Requests allowed to be in flight to a single host at once
+    #[arg(long, global = true, value_name = "N", default_value_t = 4)]
+    pub per_host: usize,
+    /// Cache responses to this directory and only download documents again when they have changed
+    #[arg(long, global = true, value_name = "DIR")]
+    pub http_cache: Option<PathBuf>,
+    /// Request wiki articles from the MediaWiki Parse API instead of scraping the rendered pages
+    #[arg(long, global = true)]
+    pub api: bool,
+    /// Request articles at the revisions recorded in this metadata file of a previous build, implies --api
+    #[arg(long, global = true, value_name = "FILE")]
+    pub pin: Option<PathBuf>,
+    /// Fetch data sets again when previous results are older than this, for example `30days`
+    #[arg(long, global = true, value_name = "DURATION", value_parser = humantime::parse_duration)]
+    pub max_age: Option<Duration>,
+    /// Directory of the SIL code tables of ISO 639-3, `iso-639-3.tab` and `iso-639-3-macrolanguages.tab`, to cover every language
+    #[arg(long = "iso-639-3", global = true, value_name = "DIR")]
+    pub iso_639_3: Option<PathBuf>,
+    /// Fail the build if any UN member is missing some of its data
+    #[arg(long, global = true)]
+    pub strict: bool,
+    /// More logging, repeat for even more
+    #[arg(short, long, global = true, action = ArgAction::Count, conflicts_with = "quiet")]
+    pub verbose: u8,
+    /// Less logging, repeat for even less
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub quiet: u8,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Fetch given data sets again, data sets they depend on are read from the output directory
+    Fetch {
+        #[arg(required = true, value_name = "DATASET")]
+        datasets: Vec<DatasetKind>,
+    },
+    /// Build data sets, using previous results from the output directory when available
+    Build(Build),
+    /// Export the data sets of the output directory to another format
+    #[command(subcommand)]
+    Export(Export),
+    /// List what changed in the data sets between two output directories
+    Diff(Diff),
+}
+
+#[derive(Debug, Args)]
+pub struct Build {
+    /// `all` or names of the data sets to build
+    #[arg(required = true, value_name = "TARGET")]
+    pub targets: Vec<Target>,
+    /// Ignore previous results and fetch everything that is built again
+    #[arg(short, long)]
+    pub force: bool,
+    /// Build only these data sets, comma separated
+    #[arg(long, value_name = "DATASET", value_delimiter = ',')]
+    pub only: Vec<DatasetKind>,
+    /// Don't build these data sets, comma separated
+    #[arg(long, value_name = "DATASET", value_delimiter = ',')]
+    pub skip: Vec<DatasetKind>,
+}
+
+#[derive(Debug, Args)]
+pub struct Diff {
+    /// Output directory of the previous build, the snapshot the last build took of `--output` by default
+    pub previous: Option<PathBuf>,
+    /// Output directory of the build compared to it, `--output` by default
+    pub current: Option<PathBuf>,
+    /// Write the changes as a JSON Patch of each data set to this file
+    #[arg(long, value_name = "FILE")]
+    pub patch: Option<PathBuf>,
+    /// Don't compare these data sets, comma separated
+    #[arg(long, value_name = "DATASET", value_delimiter = ',')]
+    pub skip: Vec<DatasetKind>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Export {
+    /// SQLite database with a table for each data set, linked with foreign keys
+    Sqlite {
+        /// Database file to write, replaced if it exists
+        #[arg(default_value = "wikibase.sqlite")]
+        path: PathBuf,
+    },
+    /// Rust module with a `Country` enum and `const fn` lookups of its data
+    Rust {
+        /// Source file to write
+        #[arg(default_value = "countries.rs")]
+        path: PathBuf,
+    },
+    /// A CSV file for each data set, one row per item
+    Csv {
+        /// Directory the files are written to
+        #[arg(default_value = "csv")]
+        dir: PathBuf,
+        /// Separate columns with tabs instead of commas
+        #[arg(long)]
+        tsv: bool,
+        /// Text between the values of a column holding several, such as top level domains
+        #[arg(long, default_value = ";")]
+        separator: String,
+    },
+    /// TypeScript declarations of the data sets and a module exporting them
+    Typescript {
+        /// Directory `types.d.ts` and `data.ts` are written to
+        #[arg(default_value = "typescript")]
+        dir: PathBuf,
+    },
+    /// Python module with a `TypedDict` of each type and the data sets as constants
+    Python {
+        /// Source file to write
+        #[arg(default_value = "wikibase.py")]
+        path: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Target {
+    All,
+    Dataset(DatasetKind),
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().eq_ignore_ascii_case("all") {
+            true => Ok(Self::All),
+            false => DatasetKind::from_str(s).map(Self::Dataset),
+        }
+    }
+}
+
+impl Cli {
+    pub fn log_level(&self) -> Level {
+        match self.verbose as i8 - self.quiet as i8 {
+            i8::MIN..=-2 => Level::ERROR,
+            -1 => Level::WARN,
+            0 => Level::INFO,
+            1 => Level::DEBUG,
+            2..=i8::MAX => Level::TRACE,
+        }
+    }
+}
+
+/// Process exit code for each kind of failure
+pub fn exit_code(kind: ErrorKind) -> i32 {
+    match kind {
+        ErrorKind::Config => 2,
+        ErrorKind::Network => 3,
+        ErrorKind::Layout => 4,
+        ErrorKind::Row => 5,
+        ErrorKind::Validation => 6,
+        ErrorKind::Io => 7,
+        ErrorKind::Json => 8,
+        ErrorKind::Export => 9,
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fmt::Result as Formatted;
+
+use serde::Serialize;
+
+use crate::types::Identifier;
+use crate::Dataset;
+
+
+/// Data every UN member state is expected to have
+#[derive(Debug, Clone, Copy, Serialize, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum Requirement {
+    Flag,
+    Emoji,
+    Currency,
+    CallingCode,
+    Capital,
+    OfficialLanguage,
+}
+
+impl Requirement {
+    pub const ALL: [Self; 6] = [
+        Self::Flag, Self::Emoji, Self::Currency, Self::CallingCode, Self::Capital, Self::OfficialLanguage
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Flag => "flag",
+            Self::Emoji => "emoji",
+            Self::Currency => "currency",
+            Self::CallingCode => "calling_code",
+            Self::Capital => "capital",
+            Self::OfficialLanguage => "official_language",
+        }
+    }
+    // Column width in the gap matrix
+    fn width(&self) -> usize {
+        self.name().len().max("missing".len())
+    }
+    fn met(&self, id: &Identifier, data: &Dataset) -> bool {
+        match self {
+            Self::Flag => data.flags.contains_key(id),
+            Self::Emoji => data.emojis.contains_key(id),
+            Self::Currency => data.currencies.values().any(|c|c.regions.contains(id)),
+            Self::CallingCode => data.calling_codes.contains_key(id),
+            Self::Capital => data.capitals.contains_key(id),
+            Self::OfficialLanguage => data.languages.values().any(|l|l.is_official_in(id)),
+        }
+    }
+}
+
+/// UN member regions missing some of the data they're expected to have
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Coverage {
+    /// Number of UN member regions checked
+    pub checked: usize,
+    pub gaps: BTreeMap<Identifier, BTreeSet<Requirement>>,
+}
+
+impl Coverage {
+    /// Checks every UN member region of the data set for each requirement
+    pub fn check(data: &Dataset) -> Self {
+        let members = data.regions.iter()
+            .filter(|(_, r)|r.un_member)
+            .map(|(i, _)|i)
+            .collect::<Vec<&Identifier>>();
+
+        let gaps = members.iter()
+            .map(|i|(
+                (*i).to_owned(),
+                Requirement::ALL.into_iter().filter(|r|!r.met(i, data)).collect::<BTreeSet<Requirement>>()
+            ))
+            .filter(|(_, m)|!m.is_empty())
+            .collect();
+
+        Self { checked: members.len(), gaps }
+    }
+    pub fn is_complete(&self) -> bool {
+        self.gaps.is_empty()
+    }
+}
+
+impl Display for Coverage {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        write!(f, "{:<8}", "region")?;
+
+        for r in Requirement::ALL {
+            write!(f, "  {:>w$}", r.name(), w = r.width())?;
+        }
+
+        writeln!(f)?;
+
+        for (id, missing) in &self.gaps {
+            write!(f, "{:<8}", id.as_str())?;
+
+            for r in Requirement::ALL {
+                let mark = match missing.contains(&r) {
+                    true => "missing",
+                    false => "ok",
+                };
+
+                write!(f, "  {:>w$}", mark, w = r.width())?;
+            }
+
+            writeln!(f)?;
+        }
+
+        writeln!(f, "{} of {} UN members have gaps", self.gaps.len(), self.checked)
+    }
+}
@@ -0,0 +1,128 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fmt::Result as Formatted;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, WikibaseError};
+use crate::types::{CallingCode, Capital, Country, Currency, Flag, Identifier, Language, Region, SovereignState, UNMember};
+
+
+/// Data sets generated by the scraper, each written to its own JSON file
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum DatasetKind {
+    UnNations,
+    SovereignStates,
+    Regions,
+    Flags,
+    Emojis,
+    Currencies,
+    CallingCodes,
+    Languages,
+    Capitals,
+    Countries,
+}
+
+impl Display for DatasetKind {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for DatasetKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace('-', "_");
+
+        Self::ALL.into_iter()
+            .find(|k|k.name() == name)
+            .ok_or(format!(
+                "Unknown data set '{}', expected one of: {}",
+                s,
+                Self::ALL.map(|k|k.name()).join(", ")
+            ))
+    }
+}
+
+impl DatasetKind {
+    /// Every data set in the order they can be built in
+    pub const ALL: [Self; 10] = [
+        Self::UnNations, Self::SovereignStates, Self::Regions, Self::Flags, Self::Emojis,
+        Self::Currencies, Self::CallingCodes, Self::Languages, Self::Capitals, Self::Countries
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UnNations => "un_nations",
+            Self::SovereignStates => "sovereign_states",
+            Self::Regions => "regions",
+            Self::Flags => "flags",
+            Self::Emojis => "emojis",
+            Self::Currencies => "currencies",
+            Self::CallingCodes => "calling_codes",
+            Self::Languages => "languages",
+            Self::Capitals => "capitals",
+            Self::Countries => "countries",
+        }
+    }
+    pub fn file_name(&self) -> String {
+        format!("{}.json", self.name())
+    }
+    /// File the JSON Schema of the data set is written to
+    pub fn schema_file_name(&self) -> String {
+        format!("{}.schema.json", self.name())
+    }
+}
+
+/// All data sets generated by the scraper, read from an output directory
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    pub un_nations: Vec<UNMember>,
+    pub sovereign_states: BTreeMap<Identifier, SovereignState>,
+    pub regions: BTreeMap<Identifier, Region>,
+    pub flags: BTreeMap<Identifier, Flag>,
+    pub emojis: BTreeMap<Identifier, String>,
+    pub currencies: BTreeMap<Identifier, Currency>,
+    pub calling_codes: BTreeMap<Identifier, CallingCode>,
+    pub languages: BTreeMap<Identifier, Language>,
+    pub capitals: BTreeMap<Identifier, Capital>,
+    pub countries: BTreeMap<Identifier, Country>,
+}
+
+impl Dataset {
+    /// Reads every JSON file the scraper writes from `dir`, fails if any of them is missing or invalid
+    pub fn load_from_dir<P>(dir: P) -> Result<Self> where P: AsRef<Path> {
+        let dir = dir.as_ref();
+
+        Ok(Self {
+            un_nations: read_json(dir, DatasetKind::UnNations)?,
+            sovereign_states: read_json(dir, DatasetKind::SovereignStates)?,
+            regions: read_json(dir, DatasetKind::Regions)?,
+            flags: read_json(dir, DatasetKind::Flags)?,
+            emojis: read_json(dir, DatasetKind::Emojis)?,
+            currencies: read_json(dir, DatasetKind::Currencies)?,
+            calling_codes: read_json(dir, DatasetKind::CallingCodes)?,
+            languages: read_json(dir, DatasetKind::Languages)?,
+            capitals: read_json(dir, DatasetKind::Capitals)?,
+            countries: read_json(dir, DatasetKind::Countries)?,
+        })
+    }
+}
+
+fn read_json<T>(dir: &Path, kind: DatasetKind) -> Result<T> where T: DeserializeOwned {
+    let path = dir.join(kind.file_name());
+
+    let data = read_to_string(&path).map_err(|e|
+        WikibaseError::io("Failed to read", &path, e)
+    )?;
+
+    serde_json::from_str(&data).map_err(|e|
+        WikibaseError::json(path.to_string_lossy(), e)
+    )
+}
@@ -0,0 +1,213 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fmt::Result as Formatted;
+use std::fs::read_to_string;
+use std::io::ErrorKind as IoErrorKind;
+use std::path::Path;
+
+use serde_json::{json, Value};
+use tokio::fs::{copy, create_dir_all, remove_file};
+
+use crate::error::{Result, WikibaseError};
+use crate::DatasetKind;
+
+/// Directory within the output directory the data sets of the previous build are kept in
+pub const PREVIOUS_DIR: &str = ".previous";
+
+
+/// Value of a data set that was added, removed or replaced between two builds
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub kind: DatasetKind,
+    /// Keys and indexes from the root of the data set to the value, empty for the data set itself
+    pub path: Vec<String>,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+impl Change {
+    /// JSON Pointer to the value within its data set file
+    pub fn pointer(&self) -> String {
+        self.path.iter().map(|p|format!("/{}", p.replace('~', "~0").replace('/', "~1"))).collect()
+    }
+    // Path as the changelog shows it, `fi.capital.name`
+    fn key(&self) -> String {
+        self.path.join(".")
+    }
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        match (&self.old, &self.new) {
+            (None, _) if self.path.is_empty() => write!(f, "+ data set added"),
+            (_, None) if self.path.is_empty() => write!(f, "- data set removed"),
+            (None, Some(n)) => write!(f, "+ {}: {}", self.key(), summary(n)),
+            (Some(o), None) => write!(f, "- {}: {}", self.key(), summary(o)),
+            // Sets such as the regions of a currency, only the items that differ
+            (Some(Value::Array(o)), Some(Value::Array(n))) if scalars(o) && scalars(n) => {
+                let added = n.iter().filter(|v|!o.contains(v)).map(|v|format!("+{}", scalar(v)));
+                let removed = o.iter().filter(|v|!n.contains(v)).map(|v|format!("-{}", scalar(v)));
+                let items = added.chain(removed).collect::<Vec<String>>();
+
+                match items.is_empty() {
+                    true => write!(f, "~ {}: reordered", self.key()),
+                    false => write!(f, "~ {}: {}", self.key(), items.join(" ")),
+                }
+            },
+            (Some(o), Some(n)) => write!(f, "~ {}: {} -> {}", self.key(), summary(o), summary(n)),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+/// Changes in the data sets between two builds
+#[derive(Debug, Clone, Default)]
+pub struct Diff {
+    pub changes: Vec<Change>,
+}
+
+impl Display for Diff {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        if self.changes.is_empty() {
+            return writeln!(f, "No changes")
+        }
+
+        let mut kind = None;
+
+        for c in &self.changes {
+            if kind != Some(c.kind) {
+                writeln!(f, "{}", c.kind)?;
+                kind = Some(c.kind);
+            }
+
+            writeln!(f, "  {}", c)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Diff {
+    /// Compares given data sets of the output directories `previous` and `current`, files missing from either are added or removed
+    pub fn read<P, C, I>(previous: P, current: C, kinds: I) -> Result<Self>
+    where P: AsRef<Path>, C: AsRef<Path>, I: IntoIterator<Item = DatasetKind> {
+        let mut diff = Self::default();
+
+        for kind in kinds {
+            let old = read_json(previous.as_ref(), kind)?;
+            let new = read_json(current.as_ref(), kind)?;
+
+            diff.compare(kind, old.as_ref(), new.as_ref());
+        }
+
+        Ok(diff)
+    }
+    /// Adds the changes between two versions of a data set
+    pub fn compare(&mut self, kind: DatasetKind, old: Option<&Value>, new: Option<&Value>) {
+        compare(kind, &mut Vec::new(), old, new, &mut self.changes);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    /// JSON Patch (RFC 6902) of each changed data set by its name, each applies to the file of its data set
+    pub fn patch(&self) -> Value {
+        let mut patches = BTreeMap::<&str, Vec<Value>>::new();
+
+        for c in &self.changes {
+            let op = match (&c.old, &c.new) {
+                (None, Some(n)) => json!({ "op": "add", "path": c.pointer(), "value": n }),
+                // Root of a document can't be removed, a removed data set is replaced with null
+                (Some(_), None) if c.path.is_empty() => json!({ "op": "replace", "path": "", "value": null }),
+                (Some(_), None) => json!({ "op": "remove", "path": c.pointer() }),
+                (_, n) => json!({ "op": "replace", "path": c.pointer(), "value": n }),
+            };
+
+            patches.entry(c.kind.name()).or_default().push(op);
+        }
+
+        json!(patches)
+    }
+}
+
+/// Copies a data set of the output directory to its `PREVIOUS_DIR` just before a build replaces it, so the build can be compared with the one before.
+/// Data sets read from previous results are left alone, the snapshot keeps the version their last build replaced.
+pub async fn snapshot(dir: &Path, kind: DatasetKind) -> Result<()> {
+    let previous = dir.join(PREVIOUS_DIR);
+    let (from, to) = (dir.join(kind.file_name()), previous.join(kind.file_name()));
+
+    create_dir_all(&previous).await.map_err(|e|WikibaseError::io("Failed to create directory", &previous, e))?;
+
+    match copy(&from, &to).await {
+        Ok(_) => Ok(()),
+        // Data set the previous build didn't have is missing from the snapshot as well
+        Err(e) if e.kind() == IoErrorKind::NotFound => match remove_file(&to).await {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == IoErrorKind::NotFound => Ok(()),
+            Err(e) => Err(WikibaseError::io("Failed to remove", &to, e)),
+        },
+        Err(e) => Err(WikibaseError::io("Failed to copy", &from, e)),
+    }
+}
+
+fn compare(kind: DatasetKind, path: &mut Vec<String>, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Some(Value::Object(o)), Some(Value::Object(n))) => {
+            // Keys of both in order, the way data sets are written
+            let mut keys = o.keys().chain(n.keys()).collect::<Vec<&String>>();
+            keys.sort();
+            keys.dedup();
+
+            for k in keys {
+                path.push(k.clone());
+                compare(kind, path, o.get(k), n.get(k), changes);
+                path.pop();
+            }
+        },
+        // Lists of objects changed item by item as long as none was added or removed, anything else is replaced whole
+        (Some(Value::Array(o)), Some(Value::Array(n))) if o.len() == n.len() && !scalars(o) => {
+            for (i, (o, n)) in o.iter().zip(n).enumerate() {
+                path.push(i.to_string());
+                compare(kind, path, Some(o), Some(n), changes);
+                path.pop();
+            }
+        },
+        (o, n) if o != n => changes.push(Change {
+            kind,
+            path: path.clone(),
+            old: o.cloned(),
+            new: n.cloned(),
+        }),
+        _ => (),
+    }
+}
+
+fn read_json(dir: &Path, kind: DatasetKind) -> Result<Option<Value>> {
+    let path = dir.join(kind.file_name());
+
+    let data = match read_to_string(&path) {
+        Ok(d) => d,
+        Err(e) if e.kind() == IoErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(WikibaseError::io("Failed to read", &path, e)),
+    };
+
+    serde_json::from_str(&data).map(Some).map_err(|e|WikibaseError::json(path.to_string_lossy(), e))
+}
+
+fn scalars(values: &[Value]) -> bool {
+    values.iter().all(|v|!v.is_array() && !v.is_object())
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        v => v.to_string(),
+    }
+}
+
+// Objects by their name when they have one, the changelog would be unreadable with whole regions in it
+fn summary(value: &Value) -> String {
+    match value.get("name") {
+        Some(Value::String(n)) => n.clone(),
+        _ => value.to_string(),
+    }
+}
@@ -0,0 +1,116 @@
+use std::io;
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use crate::DatasetKind;
+
+
+pub type Result<T, E = WikibaseError> = std::result::Result<T, E>;
+
+/// Everything that can go wrong while collecting data sets
+#[derive(Debug, Error)]
+pub enum WikibaseError {
+    /// Document could not be requested or the response was not usable
+    #[error("Failed to fetch {url}: {reason}")]
+    Network {
+        url: String,
+        /// Http status of the last response, if there was one
+        status: Option<u16>,
+        reason: String,
+    },
+    /// Page doesn't look like the parser expects, most likely wikipedia changed its layout
+    #[error("Unexpected {dataset} page layout: {reason}")]
+    Layout {
+        dataset: DatasetKind,
+        reason: String,
+    },
+    /// Single table row could not be read
+    #[error("Failed to parse {dataset} row {row}: {reason} (in '{text}')")]
+    Row {
+        dataset: DatasetKind,
+        /// Index of the row among the rows collected from the page
+        row: usize,
+        /// Text of the offending cell
+        text: String,
+        reason: String,
+    },
+    /// Data was read but doesn't make sense
+    #[error("Invalid {dataset} data: {reason}")]
+    Validation {
+        dataset: DatasetKind,
+        reason: String,
+    },
+    #[error("{context} {}: {source}", path.to_string_lossy())]
+    Io {
+        context: String,
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Invalid JSON in {what}: {source}")]
+    Json {
+        what: String,
+        #[source]
+        source: serde_json::Error,
+    },
+    /// Data sets could not be written in another format
+    #[error("Failed to export {format} to {}: {reason}", path.to_string_lossy())]
+    Export {
+        format: &'static str,
+        path: PathBuf,
+        reason: String,
+    },
+    /// Invalid settings or arguments
+    #[error("{0}")]
+    Config(String),
+}
+
+/// Variants of [`WikibaseError`] without their context
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    Network,
+    Layout,
+    Row,
+    Validation,
+    Io,
+    Json,
+    Export,
+    Config,
+}
+
+impl WikibaseError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::Network { .. } => ErrorKind::Network,
+            Self::Layout { .. } => ErrorKind::Layout,
+            Self::Row { .. } => ErrorKind::Row,
+            Self::Validation { .. } => ErrorKind::Validation,
+            Self::Io { .. } => ErrorKind::Io,
+            Self::Json { .. } => ErrorKind::Json,
+            Self::Export { .. } => ErrorKind::Export,
+            Self::Config(_) => ErrorKind::Config,
+        }
+    }
+    pub fn network<U: Into<String>, R: ToString>(url: U, reason: R) -> Self {
+        Self::Network { url: url.into(), status: None, reason: reason.to_string() }
+    }
+    pub fn layout<R: Into<String>>(dataset: DatasetKind, reason: R) -> Self {
+        Self::Layout { dataset, reason: reason.into() }
+    }
+    pub fn row<T: Into<String>, R: Into<String>>(dataset: DatasetKind, row: usize, text: T, reason: R) -> Self {
+        Self::Row { dataset, row, text: text.into(), reason: reason.into() }
+    }
+    pub fn validation<R: Into<String>>(dataset: DatasetKind, reason: R) -> Self {
+        Self::Validation { dataset, reason: reason.into() }
+    }
+    pub fn io<C: Into<String>, P: Into<PathBuf>>(context: C, path: P, source: io::Error) -> Self {
+        Self::Io { context: context.into(), path: path.into(), source }
+    }
+    pub fn json<W: Into<String>>(what: W, source: serde_json::Error) -> Self {
+        Self::Json { what: what.into(), source }
+    }
+    pub fn export<P: Into<PathBuf>, R: ToString>(format: &'static str, path: P, reason: R) -> Self {
+        Self::Export { format, path: path.into(), reason: reason.to_string() }
+    }
+}
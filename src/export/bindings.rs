@@ -0,0 +1,183 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value};
+
+use crate::schema::schema;
+use crate::{Dataset, DatasetKind};
+
+
+/// Type of a value as the JSON Schemas of the data sets describe it
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    String,
+    Integer,
+    Boolean,
+    /// One of the given strings, such as the status of a language
+    Enum(Vec<String>),
+    Array(Box<Type>),
+    /// Object keyed by strings, such as the data sets keyed by identifiers
+    Map(Box<Type>),
+    /// Type defined in `$defs`
+    Named(String),
+    Nullable(Box<Type>),
+    /// Anything the schemas of our types don't use
+    Any,
+}
+
+/// Field of an object type
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub ty: Type,
+    pub required: bool,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Shape {
+    Object(Vec<Field>),
+    Alias(Type),
+}
+
+/// Type defined in the schemas, bindings declare one type of each
+#[derive(Debug, Clone)]
+pub struct Definition {
+    pub name: String,
+    pub description: Option<String>,
+    pub shape: Shape,
+}
+
+/// Every type the data sets are made of by its name, the Rust models they were generated from decide the names
+pub fn definitions() -> Vec<Definition> {
+    let mut defs = BTreeMap::new();
+
+    for kind in DatasetKind::ALL {
+        if let Some(Value::Object(d)) = Value::from(schema(kind)).get("$defs") {
+            defs.extend(d.clone());
+        }
+    }
+
+    defs.into_iter().map(|(name, s)|definition(name, &s)).collect()
+}
+
+/// Type of the root of a data set file
+pub fn dataset(kind: DatasetKind) -> Type {
+    type_of(&Value::from(schema(kind)))
+}
+
+/// Name of the type of a data set, `calling_codes` becomes `CallingCodes`
+pub fn dataset_name(kind: DatasetKind) -> String {
+    kind.name().split('_')
+        .map(|w|w[..1].to_uppercase() + &w[1..])
+        .collect()
+}
+
+/// JSON of a data set, the same the data set file has
+pub fn value(data: &Dataset, kind: DatasetKind) -> serde_json::Result<Value> {
+    match kind {
+        DatasetKind::UnNations => serde_json::to_value(&data.un_nations),
+        DatasetKind::SovereignStates => serde_json::to_value(&data.sovereign_states),
+        DatasetKind::Regions => serde_json::to_value(&data.regions),
+        DatasetKind::Flags => serde_json::to_value(&data.flags),
+        DatasetKind::Emojis => serde_json::to_value(&data.emojis),
+        DatasetKind::Currencies => serde_json::to_value(&data.currencies),
+        DatasetKind::CallingCodes => serde_json::to_value(&data.calling_codes),
+        DatasetKind::Languages => serde_json::to_value(&data.languages),
+        DatasetKind::Capitals => serde_json::to_value(&data.capitals),
+        DatasetKind::Countries => serde_json::to_value(&data.countries),
+    }
+}
+
+fn definition(name: String, schema: &Value) -> Definition {
+    let shape = match schema.get("properties") {
+        Some(Value::Object(p)) => Shape::Object(fields(schema, p)),
+        _ => Shape::Alias(type_of(schema)),
+    };
+
+    Definition { name, description: description(schema), shape }
+}
+
+// Required fields in the order they're declared in, optional ones after them
+fn fields(schema: &Value, properties: &Map<String, Value>) -> Vec<Field> {
+    let required = schema.get("required").and_then(|r|r.as_array()).into_iter().flatten()
+        .filter_map(|r|r.as_str())
+        .collect::<Vec<&str>>();
+
+    let optional = properties.keys().map(|k|k.as_str()).filter(|k|!required.contains(k));
+
+    required.iter().copied().chain(optional)
+        .filter_map(|name|properties.get(name).map(|p|Field {
+            name: name.to_string(),
+            ty: type_of(p),
+            required: required.contains(&name),
+            description: description(p),
+        }))
+        .collect()
+}
+
+fn type_of(schema: &Value) -> Type {
+    if let Some(r) = schema.get("$ref").and_then(|r|r.as_str()) {
+        return Type::Named(r.trim_start_matches("#/$defs/").to_string())
+    }
+
+    if let Some(e) = strings(schema) {
+        return Type::Enum(e)
+    }
+
+    // Option of a named type
+    if let Some(Value::Array(any)) = schema.get("anyOf") {
+        let types = any.iter().filter(|s|s.get("type") != Some(&Value::from("null"))).collect::<Vec<&Value>>();
+
+        return match types[..] {
+            [t] if types.len() < any.len() => Type::Nullable(Box::new(type_of(t))),
+            [t] => type_of(t),
+            _ => Type::Any,
+        }
+    }
+
+    match schema.get("type") {
+        Some(Value::String(t)) => primitive(t, schema),
+        // Option of a primitive, such as `["string", "null"]`
+        Some(Value::Array(types)) => {
+            let types = types.iter().filter_map(|t|t.as_str()).filter(|t|*t != "null").collect::<Vec<&str>>();
+
+            match types[..] {
+                [t] => Type::Nullable(Box::new(primitive(t, schema))),
+                _ => Type::Any,
+            }
+        },
+        _ => Type::Any,
+    }
+}
+
+fn primitive(name: &str, schema: &Value) -> Type {
+    match name {
+        "string" => Type::String,
+        "integer" => Type::Integer,
+        "boolean" => Type::Boolean,
+        "array" => Type::Array(Box::new(schema.get("items").map(type_of).unwrap_or(Type::Any))),
+        "object" => Type::Map(Box::new(schema.get("additionalProperties").map(type_of).unwrap_or(Type::Any))),
+        _ => Type::Any,
+    }
+}
+
+// Values of an enum of strings, variants with a description of their own are listed as constants in `oneOf`
+fn strings(schema: &Value) -> Option<Vec<String>> {
+    let values = match (schema.get("enum"), schema.get("oneOf")) {
+        (Some(Value::Array(e)), _) => e.iter().collect::<Vec<&Value>>(),
+        (_, Some(Value::Array(one))) => one.iter()
+            .flat_map(|s|match (s.get("const"), s.get("enum")) {
+                (Some(c), _) => vec![c],
+                (_, Some(Value::Array(e))) => e.iter().collect(),
+                _ => vec![&Value::Null],
+            })
+            .collect(),
+        _ => return None,
+    };
+
+    values.into_iter().map(|v|v.as_str().map(|s|s.to_string())).collect()
+}
+
+fn description(schema: &Value) -> Option<String> {
+    schema.get("description").and_then(|d|d.as_str()).map(|d|d.to_string())
+}
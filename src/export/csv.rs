@@ -0,0 +1,175 @@
+use std::fs::{create_dir_all, File};
+use std::path::{Path, PathBuf};
+
+use ::csv::{Writer, WriterBuilder};
+use tracing::info;
+
+use crate::error::{Result, WikibaseError};
+use crate::{Dataset, DatasetKind};
+
+const FORMAT: &str = "CSV";
+
+
+/// How values are separated in the written files
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    /// Byte between the columns, `,` for CSV and tab for TSV
+    pub delimiter: u8,
+    /// Text between the values of a column holding several, such as top level domains
+    pub separator: String,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            separator: ";".to_string(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Tab separated columns
+    pub fn tsv() -> Self {
+        Self {
+            delimiter: b'\t',
+            ..Self::default()
+        }
+    }
+    fn extension(&self) -> &'static str {
+        match self.delimiter {
+            b'\t' => "tsv",
+            _ => "csv",
+        }
+    }
+}
+
+/// Writes each data set to a file of its own in `dir`, one row per item with a header row on top
+pub fn write<P: AsRef<Path>>(data: &Dataset, dir: P, options: &CsvOptions) -> Result<()> {
+    let dir = dir.as_ref();
+
+    create_dir_all(dir).map_err(|e|WikibaseError::io("Failed to create export directory", dir, e))?;
+
+    for kind in DatasetKind::ALL {
+        let path = file_path(dir, kind, options);
+        let fail = |e: ::csv::Error|WikibaseError::export(FORMAT, &path, e);
+
+        let file = File::create(&path).map_err(|e|WikibaseError::io("Failed to create", &path, e))?;
+        let mut w = WriterBuilder::new().delimiter(options.delimiter).from_writer(file);
+
+        records(&mut w, kind, data, &options.separator).map_err(fail)?;
+        w.flush().map_err(|e|WikibaseError::io("Failed to write", &path, e))?;
+    }
+
+    info!("Data sets exported to {}", dir.to_string_lossy());
+    Ok(())
+}
+
+/// Path of the file a data set is written to with given options
+pub fn file_path<P: AsRef<Path>>(dir: P, kind: DatasetKind, options: &CsvOptions) -> PathBuf {
+    dir.as_ref().join(format!("{}.{}", kind.name(), options.extension()))
+}
+
+fn records(w: &mut Writer<File>, kind: DatasetKind, data: &Dataset, separator: &str) -> ::csv::Result<()> {
+    match kind {
+        DatasetKind::UnNations => {
+            w.write_record(["name", "iso_3166"])?;
+
+            for m in &data.un_nations {
+                w.write_record([m.name.as_str(), m.iso_3166.as_ref().map(|i|i.as_str()).unwrap_or("")])?;
+            }
+        },
+        DatasetKind::SovereignStates => {
+            w.write_record(["id", "name_short", "name_long", "un_member", "disputed"])?;
+
+            for (id, s) in &data.sovereign_states {
+                w.write_record([id.as_str(), &s.name_short, &s.name_long, &s.un_member.to_string(), &s.disputed.to_string()])?;
+            }
+        },
+        DatasetKind::Regions => {
+            w.write_record(["id", "name", "state_name", "un_member", "sovereignity", "a2", "a3", "num", "iso_3166_2", "tld"])?;
+
+            for (id, r) in &data.regions {
+                w.write_record([
+                    id.as_str(), &r.name, &r.state_name, &r.un_member.to_string(), r.sovereignity.as_str(),
+                    &r.iso_3166_1.a2, &r.iso_3166_1.a3, &r.iso_3166_1.num.to_string(), &r.iso_3166_2.0,
+                    &r.tld.0.join(separator)
+                ])?;
+            }
+        },
+        DatasetKind::Flags => {
+            w.write_record(["id", "sovereignity", "dir"])?;
+
+            for (id, f) in &data.flags {
+                w.write_record([id.as_str(), f.sovereignity.as_str(), &f.dir.to_string_lossy()])?;
+            }
+        },
+        DatasetKind::Emojis => {
+            w.write_record(["id", "emoji"])?;
+
+            for (id, e) in &data.emojis {
+                w.write_record([id.as_str(), e])?;
+            }
+        },
+        DatasetKind::Currencies => {
+            w.write_record(["code", "name", "symbol", "fraction", "fraction_basic", "region"])?;
+
+            // One row for each region the currency circulates in
+            for (code, c) in &data.currencies {
+                for r in &c.regions {
+                    w.write_record([
+                        code.as_str(), &c.name, &c.symbol, &c.fraction.name, &c.fraction.basic.to_string(), r.as_str()
+                    ])?;
+                }
+            }
+        },
+        DatasetKind::CallingCodes => {
+            w.write_record(["id", "country_code", "area_codes", "zone"])?;
+
+            for (id, c) in &data.calling_codes {
+                w.write_record([id.as_str(), &c.country_code.to_string(), &c.area_codes.join(separator), &c.zone.to_string()])?;
+            }
+        },
+        DatasetKind::Languages => {
+            w.write_record([
+                "code", "name_short", "name_long", "iso_639_1", "iso_639_2_t", "iso_639_2_b", "iso_639_3",
+                "scope", "type", "macrolanguage", "regions"
+            ])?;
+
+            for (code, l) in &data.languages {
+                w.write_record([
+                    code.as_str(), &l.name_short, &l.name_long,
+                    l.iso639.set1(), l.iso639.set2_t(), l.iso639.set2_b(), l.iso639.set3(),
+                    l.scope.map(|s|s.name()).unwrap_or_default(),
+                    l.language_type.map(|t|t.name()).unwrap_or_default(),
+                    l.macrolanguage.as_ref().map(|m|m.as_str()).unwrap_or_default(),
+                    &l.regions.iter().map(|r|r.region.as_str()).collect::<Vec<&str>>().join(separator)
+                ])?;
+            }
+        },
+        DatasetKind::Capitals => {
+            w.write_record(["id", "name", "endonyms"])?;
+
+            for (id, c) in &data.capitals {
+                w.write_record([id.as_str(), &c.name, &c.endonyms.as_deref().unwrap_or_default().join(separator)])?;
+            }
+        },
+        DatasetKind::Countries => {
+            // Codes of the joined items only, their details are in the files above
+            w.write_record(["id", "name", "a2", "capital", "calling_code", "emoji", "currencies", "languages"])?;
+
+            for (id, c) in &data.countries {
+                w.write_record([
+                    id.as_str(), &c.region.name, &c.region.iso_3166_1.a2,
+                    c.capital.as_ref().map(|c|c.name.as_str()).unwrap_or(""),
+                    &c.calling_code.as_ref().map(|c|c.to_string()).unwrap_or_default(),
+                    c.emoji.as_deref().unwrap_or(""),
+                    &c.currencies.keys().map(|k|k.as_str()).collect::<Vec<&str>>().join(separator),
+                    &c.languages.keys().map(|k|k.as_str()).collect::<Vec<&str>>().join(separator),
+                ])?;
+            }
+        },
+    }
+
+    Ok(())
+}
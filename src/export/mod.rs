@@ -0,0 +1,8 @@
+#[cfg(feature = "csv")]
+pub mod csv;
+pub mod python;
+pub mod rust;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+pub mod typescript;
+mod bindings;
@@ -0,0 +1,175 @@
+use std::fmt::Write as _;
+use std::fs::write;
+use std::path::Path;
+
+use serde_json::Value;
+use tracing::info;
+
+use super::bindings::{dataset, dataset_name, definitions, value, Shape, Type};
+use crate::error::{Result, WikibaseError};
+use crate::{Dataset, DatasetKind};
+
+const INDENT: &str = "    ";
+
+
+/// Writes a Python module with types of the data sets and the data sets as constants to `path`
+pub fn write_module<P: AsRef<Path>>(data: &Dataset, path: P) -> Result<()> {
+    let path = path.as_ref();
+
+    write(path, generate(data)?).map_err(|e|WikibaseError::io("Failed to write Python module to", path, e))?;
+
+    info!("Data sets exported to {}", path.to_string_lossy());
+    Ok(())
+}
+
+/// Python source of the types, followed by a constant of each data set
+pub fn generate(data: &Dataset) -> Result<String> {
+    let mut src = declarations();
+    let s = &mut src;
+
+    for kind in DatasetKind::ALL {
+        let value = value(data, kind).map_err(|e|WikibaseError::json(format!("{} data", kind), e))?;
+
+        line(s, "");
+        line(s, "");
+        let _ = write!(s, "{}: {} = ", kind.name().to_uppercase(), dataset_name(kind));
+        literal(s, &value, 0);
+        line(s, "");
+    }
+
+    Ok(src)
+}
+
+/// `TypedDict` of every object type in the data sets, aliases of the rest and a type of each data set
+pub fn declarations() -> String {
+    let mut src = String::new();
+    let s = &mut src;
+
+    line(s, "# Generated by wikibase from the JSON Schemas of the data sets, do not edit");
+    line(s, "# Requires Python 3.8 or later, and typing_extensions before Python 3.11");
+    line(s, "");
+    line(s, "from __future__ import annotations");
+    line(s, "");
+    line(s, "from typing import Any, Dict, List, Literal, Optional");
+    line(s, "");
+    // NotRequired is in typing since 3.11, and TypedDict honors it only from there on
+    line(s, "try:");
+    line(s, "    from typing import NotRequired, TypeAlias, TypedDict");
+    line(s, "except ImportError:");
+    line(s, "    from typing_extensions import NotRequired, TypeAlias, TypedDict");
+
+    for d in definitions() {
+        line(s, "");
+        line(s, "");
+
+        match d.shape {
+            // Aliases are evaluated right away, types declared further down are referred to by name
+            Shape::Alias(t) if named(&t) => {
+                comment(s, "", d.description.as_deref());
+                line(s, &format!("{}: TypeAlias = \"{}\"", d.name, ty(&t)));
+            },
+            Shape::Alias(t) => {
+                comment(s, "", d.description.as_deref());
+                line(s, &format!("{}: TypeAlias = {}", d.name, ty(&t)));
+            },
+            Shape::Object(fields) => {
+                line(s, &format!("class {}(TypedDict):", d.name));
+
+                if let Some(description) = &d.description {
+                    line(s, &format!("{}\"\"\"{}\"\"\"", INDENT, description.replace('\n', " ")));
+                    line(s, "");
+                }
+
+                for f in fields {
+                    let t = match f.required {
+                        true => ty(&f.ty),
+                        false => format!("NotRequired[{}]", ty(&f.ty)),
+                    };
+
+                    comment(s, INDENT, f.description.as_deref());
+                    line(s, &format!("{}{}: {}", INDENT, f.name, t));
+                }
+            },
+        }
+    }
+
+    line(s, "");
+    line(s, "");
+    for kind in DatasetKind::ALL {
+        line(s, &format!("# Contents of {}", kind.file_name()));
+        line(s, &format!("{}: TypeAlias = {}", dataset_name(kind), ty(&dataset(kind))));
+    }
+
+    src
+}
+
+fn line(s: &mut String, text: &str) {
+    let _ = writeln!(s, "{}", text);
+}
+
+fn comment(s: &mut String, indent: &str, description: Option<&str>) {
+    if let Some(d) = description {
+        line(s, &format!("{}# {}", indent, d.replace('\n', " ")));
+    }
+}
+
+fn ty(t: &Type) -> String {
+    match t {
+        Type::String => "str".to_string(),
+        Type::Integer => "int".to_string(),
+        Type::Boolean => "bool".to_string(),
+        Type::Enum(e) => format!("Literal[{}]", e.iter().map(|v|Value::from(v.as_str()).to_string()).collect::<Vec<String>>().join(", ")),
+        Type::Array(t) => format!("List[{}]", ty(t)),
+        Type::Map(t) => format!("Dict[str, {}]", ty(t)),
+        Type::Named(n) => n.clone(),
+        Type::Nullable(t) => format!("Optional[{}]", ty(t)),
+        Type::Any => "Any".to_string(),
+    }
+}
+
+fn named(t: &Type) -> bool {
+    match t {
+        Type::Array(t) | Type::Map(t) | Type::Nullable(t) => named(t),
+        Type::Named(_) => true,
+        _ => false,
+    }
+}
+
+// JSON as a Python literal, strings and numbers are written the same in both
+fn literal(s: &mut String, value: &Value, depth: usize) {
+    let indent = INDENT.repeat(depth + 1);
+
+    match value {
+        Value::Null => s.push_str("None"),
+        Value::Bool(true) => s.push_str("True"),
+        Value::Bool(false) => s.push_str("False"),
+        Value::Number(n) => s.push_str(&n.to_string()),
+        Value::String(v) => s.push_str(&Value::from(v.as_str()).to_string()),
+        Value::Array(a) if a.is_empty() => s.push_str("[]"),
+        Value::Array(a) => {
+            s.push_str("[\n");
+
+            for v in a {
+                s.push_str(&indent);
+                literal(s, v, depth + 1);
+                s.push_str(",\n");
+            }
+
+            s.push_str(&INDENT.repeat(depth));
+            s.push(']');
+        },
+        Value::Object(o) if o.is_empty() => s.push_str("{}"),
+        Value::Object(o) => {
+            s.push_str("{\n");
+
+            for (k, v) in o {
+                let _ = write!(s, "{}{}: ", indent, Value::from(k.as_str()));
+                literal(s, v, depth + 1);
+                s.push_str(",\n");
+            }
+
+            s.push_str(&INDENT.repeat(depth));
+            s.push('}');
+        },
+    }
+}
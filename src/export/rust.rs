@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs::write;
+use std::path::Path;
+
+use tracing::info;
+
+use crate::error::{Result, WikibaseError};
+use crate::types::{Country, Identifier};
+use crate::Dataset;
+
+
+/// Writes a Rust module with the data sets as constants to `path`, to be vendored by other crates
+pub fn write_module<P: AsRef<Path>>(data: &Dataset, path: P) -> Result<()> {
+    let path = path.as_ref();
+
+    write(path, generate(data)).map_err(|e|WikibaseError::io("Failed to write Rust module to", path, e))?;
+
+    info!("Data sets exported to {}", path.to_string_lossy());
+    Ok(())
+}
+
+/// Rust source of a `Country` enum with a variant for each ISO 3166-1 alpha-2 code, and `const fn` lookups of its data
+pub fn generate(data: &Dataset) -> String {
+    // Variants are named after the alpha-2 code, sorted as the codes are
+    let countries = Country::from_dataset(data).into_values()
+        .map(|c|(c.region.iso_3166_1.a2.to_uppercase(), c))
+        .collect::<BTreeMap<String, Country>>();
+
+    let mut src = String::new();
+    let s = &mut src;
+
+    line(s, "// Generated by wikibase from data collected from Wikipedia, do not edit");
+    line(s, "");
+
+    // Currencies and languages once, countries refer to them by their constants
+    line(s, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]");
+    line(s, "pub struct Currency {");
+    line(s, "    /// ISO 4217 code");
+    line(s, "    pub code: &'static str,");
+    line(s, "    pub name: &'static str,");
+    line(s, "    pub symbol: &'static str,");
+    line(s, "    /// Name of the fractional unit");
+    line(s, "    pub fraction: &'static str,");
+    line(s, "    /// Number of fractional units in one");
+    line(s, "    pub fraction_basic: u16,");
+    line(s, "}");
+    line(s, "");
+    line(s, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]");
+    line(s, "pub struct Language {");
+    line(s, "    pub name: &'static str,");
+    line(s, "    pub iso_639_1: &'static str,");
+    line(s, "    pub iso_639_2_t: &'static str,");
+    line(s, "    pub iso_639_2_b: &'static str,");
+    line(s, "    pub iso_639_3: &'static str,");
+    line(s, "}");
+    line(s, "");
+    line(s, "#[derive(Debug, Clone, Copy, PartialEq, Eq)]");
+    line(s, "pub struct CallingCode {");
+    line(s, "    pub country_code: u16,");
+    line(s, "    /// National prefixes following the country code, empty when the country code alone tells the country");
+    line(s, "    pub area_codes: &'static [&'static str],");
+    line(s, "}");
+    line(s, "");
+
+    line(s, "/// Currencies by their ISO 4217 code");
+    line(s, "pub mod currency {");
+    line(s, "    use super::Currency;");
+    line(s, "");
+    for (code, c) in &data.currencies {
+        line(s, &format!(
+            "    pub const {}: Currency = Currency {{ code: {:?}, name: {:?}, symbol: {:?}, fraction: {:?}, fraction_basic: {} }};",
+            constant(code), code.as_str().to_uppercase(), c.name, c.symbol, c.fraction.name, c.fraction.basic
+        ));
+    }
+    line(s, "}");
+    line(s, "");
+
+    line(s, "/// Languages by their ISO 639-3 code");
+    line(s, "pub mod language {");
+    line(s, "    use super::Language;");
+    line(s, "");
+    for (code, l) in &data.languages {
+        line(s, &format!(
+            "    pub const {}: Language = Language {{ name: {:?}, iso_639_1: {:?}, iso_639_2_t: {:?}, iso_639_2_b: {:?}, iso_639_3: {:?} }};",
+            constant(code), l.name_short, l.iso639.set1(), l.iso639.set2_t(), l.iso639.set2_b(), l.iso639.set3()
+        ));
+    }
+    line(s, "}");
+    line(s, "");
+
+    line(s, &format!("pub const CURRENCIES: &[Currency] = {};", constants("currency", data.currencies.keys())));
+    line(s, &format!("pub const LANGUAGES: &[Language] = {};", constants("language", data.languages.keys())));
+    line(s, "");
+
+    line(s, "/// Countries by their ISO 3166-1 alpha-2 code");
+    line(s, "#[allow(clippy::upper_case_acronyms)]");
+    line(s, "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]");
+    line(s, "pub enum Country {");
+    for (a2, c) in &countries {
+        line(s, &format!("    /// {}", c.region.name));
+        line(s, &format!("    {},", a2));
+    }
+    line(s, "}");
+    line(s, "");
+
+    line(s, "impl Country {");
+    line(s, &format!("    pub const ALL: [Self; {}] = [", countries.len()));
+    for a2 in countries.keys() {
+        line(s, &format!("        Self::{},", a2));
+    }
+    line(s, "    ];");
+    line(s, "");
+
+    // Lookups by code
+    line(s, "    /// Country of an upper case ISO 3166-1 alpha-2 code");
+    line(s, "    pub const fn from_alpha2(code: &str) -> Option<Self> {");
+    line(s, "        match code.as_bytes() {");
+    for a2 in countries.keys() {
+        line(s, &format!("            b{:?} => Some(Self::{}),", a2, a2));
+    }
+    line(s, "            _ => None,");
+    line(s, "        }");
+    line(s, "    }");
+    line(s, "    /// Country of an upper case ISO 3166-1 alpha-3 code");
+    line(s, "    pub const fn from_alpha3(code: &str) -> Option<Self> {");
+    line(s, "        match code.as_bytes() {");
+    for (a2, c) in &countries {
+        line(s, &format!("            b{:?} => Some(Self::{}),", c.region.iso_3166_1.a3, a2));
+    }
+    line(s, "            _ => None,");
+    line(s, "        }");
+    line(s, "    }");
+    line(s, "    /// Country of an ISO 3166-1 numeric code");
+    line(s, "    pub const fn from_numeric(code: u16) -> Option<Self> {");
+    line(s, "        match code {");
+    for (a2, c) in &countries {
+        line(s, &format!("            {} => Some(Self::{}),", c.region.iso_3166_1.num, a2));
+    }
+    line(s, "            _ => None,");
+    line(s, "        }");
+    line(s, "    }");
+
+    // Lookups of the data of a country
+    lookup(s, &countries, "alpha2", "&'static str", |a2, _|format!("{:?}", a2));
+    lookup(s, &countries, "alpha3", "&'static str", |_, c|format!("{:?}", c.region.iso_3166_1.a3));
+    lookup(s, &countries, "numeric", "u16", |_, c|c.region.iso_3166_1.num.to_string());
+    lookup(s, &countries, "name", "&'static str", |_, c|format!("{:?}", c.region.name));
+    lookup(s, &countries, "un_member", "bool", |_, c|c.region.un_member.to_string());
+    lookup(s, &countries, "calling_code", "Option<CallingCode>", |_, c|match &c.calling_code {
+        Some(code) => format!("Some(CallingCode {{ country_code: {}, area_codes: &{:?} }})", code.country_code, code.area_codes),
+        None => "None".to_string(),
+    });
+    lookup(s, &countries, "emoji", "Option<&'static str>", |_, c|optional(c.emoji.as_deref()));
+    lookup(s, &countries, "capital", "Option<&'static str>", |_, c|optional(c.capital.as_ref().map(|c|c.name.as_str())));
+    lookup(s, &countries, "currencies", "&'static [Currency]", |_, c|constants("currency", c.currencies.keys()));
+    lookup(s, &countries, "languages", "&'static [Language]", |_, c|constants("language", c.languages.keys()));
+    lookup(s, &countries, "official_languages", "&'static [Language]", |_, c|constants("language", c.official_languages().map(|(k, _)|k)));
+
+    line(s, "}");
+
+    src
+}
+
+fn line(s: &mut String, text: &str) {
+    let _ = writeln!(s, "{}", text);
+}
+
+// Method matching every country to its value
+fn lookup<F>(s: &mut String, countries: &BTreeMap<String, Country>, name: &str, ty: &str, value: F)
+where F: Fn(&str, &Country) -> String {
+    line(s, &format!("    pub const fn {}(self) -> {} {{", name, ty));
+    line(s, "        match self {");
+
+    for (a2, c) in countries {
+        line(s, &format!("            Self::{} => {},", a2, value(a2, c)));
+    }
+
+    line(s, "        }");
+    line(s, "    }");
+}
+
+fn optional(value: Option<&str>) -> String {
+    match value {
+        Some(v) => format!("Some({:?})", v),
+        None => "None".to_string(),
+    }
+}
+
+// Name of the constant of a currency or a language, ISO codes are letters only
+fn constant(code: &Identifier) -> String {
+    code.as_str().to_uppercase()
+}
+
+// Slice of the constants of given module
+fn constants<'a, I: Iterator<Item = &'a Identifier>>(module: &str, codes: I) -> String {
+    let items = codes.map(|c|format!("{}::{}", module, constant(c))).collect::<Vec<String>>();
+
+    format!("&[{}]", items.join(", "))
+}
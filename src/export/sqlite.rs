@@ -0,0 +1,187 @@
+use std::fs::remove_file;
+use std::path::Path;
+
+use rusqlite::{params, Connection, Transaction};
+use tracing::info;
+
+use crate::error::{Result, WikibaseError};
+use crate::Dataset;
+
+const FORMAT: &str = "SQLite";
+
+// Tables are created in the order their foreign keys allow
+const SCHEMA: &str = "
+CREATE TABLE sovereign_states (
+    id TEXT PRIMARY KEY,
+    name_short TEXT NOT NULL,
+    name_long TEXT NOT NULL,
+    un_member INTEGER NOT NULL,
+    disputed INTEGER NOT NULL
+);
+
+CREATE TABLE regions (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    state_name TEXT NOT NULL,
+    un_member INTEGER NOT NULL,
+    sovereign_state TEXT NOT NULL REFERENCES sovereign_states (id),
+    a2 TEXT NOT NULL UNIQUE,
+    a3 TEXT NOT NULL UNIQUE,
+    num INTEGER NOT NULL,
+    iso_3166_2 TEXT NOT NULL,
+    tld TEXT NOT NULL,
+    emoji TEXT
+);
+
+CREATE TABLE flags (
+    sovereign_state TEXT PRIMARY KEY REFERENCES sovereign_states (id),
+    dir TEXT NOT NULL
+);
+
+CREATE TABLE currencies (
+    code TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    fraction TEXT NOT NULL,
+    fraction_basic INTEGER NOT NULL
+);
+
+CREATE TABLE currency_regions (
+    currency TEXT NOT NULL REFERENCES currencies (code),
+    region TEXT NOT NULL REFERENCES regions (id),
+    PRIMARY KEY (currency, region)
+);
+
+CREATE TABLE languages (
+    code TEXT PRIMARY KEY,
+    name_short TEXT NOT NULL,
+    name_long TEXT NOT NULL,
+    iso_639_1 TEXT NOT NULL,
+    iso_639_2_t TEXT NOT NULL,
+    iso_639_2_b TEXT NOT NULL,
+    iso_639_3 TEXT NOT NULL,
+    scope TEXT,
+    type TEXT,
+    -- Members might come before their macrolanguage
+    macrolanguage TEXT REFERENCES languages (code) DEFERRABLE INITIALLY DEFERRED
+);
+
+CREATE TABLE language_regions (
+    language TEXT NOT NULL REFERENCES languages (code),
+    region TEXT NOT NULL REFERENCES regions (id),
+    status TEXT,
+    PRIMARY KEY (language, region)
+);
+
+CREATE TABLE capitals (
+    region TEXT PRIMARY KEY REFERENCES regions (id),
+    name TEXT NOT NULL
+);
+
+CREATE TABLE capital_endonyms (
+    region TEXT NOT NULL REFERENCES capitals (region),
+    endonym TEXT NOT NULL,
+    PRIMARY KEY (region, endonym)
+);
+
+CREATE TABLE calling_codes (
+    region TEXT PRIMARY KEY REFERENCES regions (id),
+    country_code INTEGER NOT NULL,
+    area_codes TEXT NOT NULL,
+    zone INTEGER NOT NULL
+);
+";
+
+
+/// Writes the data sets to an SQLite database at `path`, replacing the file if it exists
+pub fn write<P: AsRef<Path>>(data: &Dataset, path: P) -> Result<()> {
+    let path = path.as_ref();
+    let fail = |e: rusqlite::Error|WikibaseError::export(FORMAT, path, e);
+
+    if path.exists() {
+        remove_file(path).map_err(|e|WikibaseError::io("Failed to remove previous database", path, e))?;
+    }
+
+    let mut conn = Connection::open(path).map_err(fail)?;
+
+    conn.pragma_update(None, "foreign_keys", true).map_err(fail)?;
+    conn.execute_batch(SCHEMA).map_err(fail)?;
+
+    // All or nothing, rows breaking a foreign key fail the whole export
+    let tx = conn.transaction().map_err(fail)?;
+    insert(&tx, data).map_err(fail)?;
+    tx.commit().map_err(fail)?;
+
+    info!("Data sets exported to {}", path.to_string_lossy());
+    Ok(())
+}
+
+fn insert(tx: &Transaction, data: &Dataset) -> rusqlite::Result<()> {
+    let mut stmt = tx.prepare("INSERT INTO sovereign_states VALUES (?1, ?2, ?3, ?4, ?5)")?;
+
+    for (id, s) in &data.sovereign_states {
+        stmt.execute(params![id.as_str(), s.name_short, s.name_long, s.un_member, s.disputed])?;
+    }
+
+    let mut stmt = tx.prepare("INSERT INTO regions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)")?;
+
+    for (id, r) in &data.regions {
+        stmt.execute(params![
+            id.as_str(), r.name, r.state_name, r.un_member, r.sovereignity.as_str(),
+            r.iso_3166_1.a2, r.iso_3166_1.a3, r.iso_3166_1.num, r.iso_3166_2.0, r.tld.0.join(" "),
+            data.emojis.get(id)
+        ])?;
+    }
+
+    let mut stmt = tx.prepare("INSERT INTO flags VALUES (?1, ?2)")?;
+
+    for (id, f) in &data.flags {
+        stmt.execute(params![id.as_str(), f.dir.to_string_lossy()])?;
+    }
+
+    let mut stmt = tx.prepare("INSERT INTO currencies VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    let mut regions = tx.prepare("INSERT INTO currency_regions VALUES (?1, ?2)")?;
+
+    for (code, c) in &data.currencies {
+        stmt.execute(params![code.as_str(), c.name, c.symbol, c.fraction.name, c.fraction.basic])?;
+
+        for r in &c.regions {
+            regions.execute(params![code.as_str(), r.as_str()])?;
+        }
+    }
+
+    let mut stmt = tx.prepare("INSERT INTO languages VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)")?;
+    let mut regions = tx.prepare("INSERT INTO language_regions VALUES (?1, ?2, ?3)")?;
+
+    for (code, l) in &data.languages {
+        stmt.execute(params![
+            code.as_str(), l.name_short, l.name_long,
+            l.iso639.set1(), l.iso639.set2_t(), l.iso639.set2_b(), l.iso639.set3(),
+            l.scope.map(|s|s.name()), l.language_type.map(|t|t.name()), l.macrolanguage.as_ref().map(|m|m.as_str())
+        ])?;
+
+        for r in &l.regions {
+            regions.execute(params![code.as_str(), r.region.as_str(), r.status.map(|s|s.name())])?;
+        }
+    }
+
+    let mut stmt = tx.prepare("INSERT INTO capitals VALUES (?1, ?2)")?;
+    // Same endonym is listed once for each language it's in
+    let mut endonyms = tx.prepare("INSERT OR IGNORE INTO capital_endonyms VALUES (?1, ?2)")?;
+
+    for (id, c) in &data.capitals {
+        stmt.execute(params![id.as_str(), c.name])?;
+
+        for e in c.endonyms.iter().flatten() {
+            endonyms.execute(params![id.as_str(), e])?;
+        }
+    }
+
+    let mut stmt = tx.prepare("INSERT INTO calling_codes VALUES (?1, ?2, ?3, ?4)")?;
+
+    for (id, c) in &data.calling_codes {
+        stmt.execute(params![id.as_str(), c.country_code, c.area_codes.join(" "), c.zone])?;
+    }
+
+    Ok(())
+}
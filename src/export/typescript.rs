@@ -0,0 +1,119 @@
+use std::fmt::Write as _;
+use std::fs::{self, create_dir_all};
+use std::path::Path;
+
+use serde_json::Value;
+use tracing::info;
+
+use super::bindings::{dataset, dataset_name, definitions, value, Shape, Type};
+use crate::error::{Result, WikibaseError};
+use crate::{Dataset, DatasetKind};
+
+/// File the types are declared in
+pub const DECLARATIONS: &str = "types.d.ts";
+/// File the data sets are exported from
+pub const DATA: &str = "data.ts";
+
+
+/// Writes type declarations of the data sets and a module exporting the data sets themselves to `dir`
+pub fn write<P: AsRef<Path>>(data: &Dataset, dir: P) -> Result<()> {
+    let dir = dir.as_ref();
+
+    create_dir_all(dir).map_err(|e|WikibaseError::io("Failed to create export directory", dir, e))?;
+
+    for (name, src) in [(DECLARATIONS, declarations()), (DATA, data_module(data)?)] {
+        let path = dir.join(name);
+        fs::write(&path, src).map_err(|e|WikibaseError::io("Failed to write TypeScript to", &path, e))?;
+    }
+
+    info!("Data sets exported to {}", dir.to_string_lossy());
+    Ok(())
+}
+
+/// TypeScript declarations of every type in the data sets, and a type of each data set
+pub fn declarations() -> String {
+    let mut src = String::new();
+    let s = &mut src;
+
+    line(s, "// Generated by wikibase from the JSON Schemas of the data sets, do not edit");
+
+    for d in definitions() {
+        line(s, "");
+        comment(s, "", d.description.as_deref());
+
+        match d.shape {
+            Shape::Alias(t) => line(s, &format!("export type {} = {};", d.name, ty(&t))),
+            Shape::Object(fields) => {
+                line(s, &format!("export interface {} {{", d.name));
+
+                for f in fields {
+                    comment(s, "    ", f.description.as_deref());
+                    line(s, &format!("    {}{}: {};", f.name, if f.required { "" } else { "?" }, ty(&f.ty)));
+                }
+
+                line(s, "}");
+            },
+        }
+    }
+
+    line(s, "");
+    for kind in DatasetKind::ALL {
+        line(s, &format!("/** Contents of {} */", kind.file_name()));
+        line(s, &format!("export type {} = {};", dataset_name(kind), ty(&dataset(kind))));
+    }
+
+    src
+}
+
+/// TypeScript module exporting each data set as a constant of its declared type
+pub fn data_module(data: &Dataset) -> Result<String> {
+    let mut src = String::new();
+    let s = &mut src;
+
+    let types = DatasetKind::ALL.map(dataset_name).join(", ");
+
+    line(s, "// Generated by wikibase from data collected from Wikipedia, do not edit");
+    line(s, "");
+    line(s, &format!("import type {{ {} }} from \"./types\";", types));
+
+    for kind in DatasetKind::ALL {
+        let json = value(data, kind).and_then(|v|serde_json::to_string_pretty(&v))
+            .map_err(|e|WikibaseError::json(format!("{} data", kind), e))?;
+
+        line(s, "");
+        line(s, &format!("export const {}: {} = {};", constant(kind), dataset_name(kind), json));
+    }
+
+    Ok(src)
+}
+
+fn line(s: &mut String, text: &str) {
+    let _ = writeln!(s, "{}", text);
+}
+
+fn comment(s: &mut String, indent: &str, description: Option<&str>) {
+    if let Some(d) = description {
+        line(s, &format!("{}/** {} */", indent, d.replace('\n', " ")));
+    }
+}
+
+fn ty(t: &Type) -> String {
+    match t {
+        Type::String => "string".to_string(),
+        Type::Integer => "number".to_string(),
+        Type::Boolean => "boolean".to_string(),
+        Type::Enum(e) => e.iter().map(|v|Value::from(v.as_str()).to_string()).collect::<Vec<String>>().join(" | "),
+        Type::Array(t) if matches!(**t, Type::Nullable(_)) => format!("({})[]", ty(t)),
+        Type::Array(t) => format!("{}[]", ty(t)),
+        Type::Map(t) => format!("Record<string, {}>", ty(t)),
+        Type::Named(n) => n.clone(),
+        Type::Nullable(t) => format!("{} | null", ty(t)),
+        Type::Any => "unknown".to_string(),
+    }
+}
+
+// Name of the constant of a data set, `calling_codes` becomes `callingCodes`
+fn constant(kind: DatasetKind) -> String {
+    let name = dataset_name(kind);
+    name[..1].to_lowercase() + &name[1..]
+}
@@ -1,18 +1,153 @@
+use std::sync::{Arc, Mutex};
+
 use scraper::Html;
-use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::error::Result;
+use crate::http::Client;
+use crate::mediawiki::MediaWiki;
+use crate::snapshot::Snapshots;
+
+
+/// Where http documents are read from
+#[derive(Debug, Clone)]
+pub enum Mode {
+    /// Always request documents from the network
+    Live,
+    /// Request documents from the network and store a copy of each response
+    Record(Snapshots),
+    /// Never touch the network, read previously recorded responses instead
+    Replay(Snapshots),
+}
+
+/// How wiki articles are requested
+#[derive(Debug, Clone, Default)]
+pub enum Backend {
+    /// Scrape the rendered article
+    #[default]
+    Scrape,
+    /// Ask the MediaWiki Parse API for the parsed article, other documents are requested as they are
+    Api(MediaWiki),
+}
+
+/// Document a data set was read from
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Source {
+    pub url: String,
+    /// Wikipedia revision id of the page at the time it was fetched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<u64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Fetcher {
+    mode: Mode,
+    backend: Backend,
+    client: Client,
+    // Html documents fetched so far, shared between clones of the fetcher
+    sources: Arc<Mutex<Vec<Source>>>,
+}
+
+impl Default for Fetcher {
+    fn default() -> Self {
+        Self::new(Mode::Live)
+    }
+}
+
+impl Fetcher {
+    pub fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            backend: Backend::default(),
+            client: Client::default(),
+            sources: Arc::default(),
+        }
+    }
+    pub fn with_backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+    pub fn mode(&self) -> &Mode {
+        &self.mode
+    }
+    pub fn backend(&self) -> &Backend {
+        &self.backend
+    }
+    /// Fetcher with the same settings which keeps its own list of sources
+    pub fn tracked(&self) -> Self {
+        Self::new(self.mode.clone())
+            .with_backend(self.backend.clone())
+            .with_client(self.client.clone())
+    }
+    /// Html documents fetched with this fetcher and its clones
+    pub fn sources(&self) -> Vec<Source> {
+        self.sources.lock().map(|s|s.clone()).unwrap_or_default()
+    }
+    pub async fn get_html(&self, url: &str) -> Result<Html> {
+        let request = match &self.backend {
+            Backend::Api(a) => a.request(url),
+            Backend::Scrape => None,
+        };
+
+        let (html, revision) = match request {
+            Some(r) => {
+                debug!("Requesting {} from MediaWiki API: {}", url, r);
+
+                let page = MediaWiki::parse(url, &self.get_bytes(&r).await?)?;
+
+                (page.html, Some(page.revision))
+            },
+            None => {
+                let bytes = self.get_bytes(url).await?;
+                let html = String::from_utf8_lossy(&bytes).into_owned();
+                let revision = revision_id(&html);
+
+                (html, revision)
+            },
+        };
+
+        if let Ok(mut s) = self.sources.lock() {
+            s.push(Source {
+                url: url.to_string(),
+                revision,
+            });
+        }
 
+        // Use scraper to build readable html from response data
+        Ok(Html::parse_document(&html))
+    }
+    pub async fn get_bytes<T>(&self, url: T) -> Result<Vec<u8>> where T: AsRef<str> {
+        let url = url.as_ref();
 
-pub async fn get_html(url: &str) -> Result<Html> {
-    let resp = reqwest::get(url).await.map_err(|e| anyhow!("Failed to open http document from: {}", e))?;
-    let html = resp.text().await.map_err(|e| anyhow!("Failed to open http document from: {}", e))?;
+        match &self.mode {
+            Mode::Live => self.client.get(url).await,
+            Mode::Record(s) => {
+                let bytes = self.client.get(url).await?;
+                s.write(url, &bytes).await?;
 
-    // Use scraper to build readable html from response data
-    Ok(Html::parse_document(&html))
+                debug!("Recorded snapshot of {}", url);
+                Ok(bytes)
+            },
+            Mode::Replay(s) => {
+                debug!("Replaying snapshot of {}", url);
+                s.read(url).await
+            },
+        }
+    }
 }
 
-pub async fn get_bytes<T>(url: T) -> Result<Vec<u8>> where T: AsRef<str> {
-    let resp = reqwest::get(url.as_ref()).await.map_err(|e| anyhow!("Failed to open http document from: {}", e))?;
-    let bytes = resp.bytes().await.map_err(|e| anyhow!("Failed to open http document from: {}", e))?;
+/// Reads the revision id wikipedia embeds in the page config of every article
+pub fn revision_id(html: &str) -> Option<u64> {
+    let start = html.find("\"wgRevisionId\":")? + "\"wgRevisionId\":".len();
+    let digits = html[start..].trim_start()
+        .chars()
+        .take_while(|c|c.is_ascii_digit())
+        .collect::<String>();
 
-    Ok(bytes.to_vec())
+    digits.parse().ok().filter(|r|*r > 0)
 }
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use tokio::fs::{read_to_string, write};
+use tokio::sync::Semaphore;
+use tokio::time::sleep;
+use tracing::{debug, warn};
+
+use crate::error::{Result, WikibaseError};
+use crate::snapshot::Snapshots;
+
+
+/// Settings of the http client every document is requested with
+#[derive(Debug, Clone)]
+pub struct ClientConfig {
+    /// Wikimedia requires clients to identify themselves
+    pub user_agent: String,
+    pub timeout: Duration,
+    /// Attempts after the first one for timeouts, connection errors and retryable statuses
+    pub retries: u32,
+    /// Delay before the first retry, doubled for every retry after that
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+    /// Requests allowed to be in flight to a single host at once
+    pub per_host: usize,
+    /// Directory where responses are cached for conditional requests
+    pub cache: Option<PathBuf>,
+}
+
+impl Default for ClientConfig {
+    fn default() -> Self {
+        Self {
+            user_agent: format!("wikibase/{} (https://github.com/hienohomma/wikibase)", env!("CARGO_PKG_VERSION")),
+            timeout: Duration::from_secs(30),
+            retries: 4,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            per_host: 4,
+            cache: None,
+        }
+    }
+}
+
+// Validators of a cached response, stored next to the response body
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Validators {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+}
+
+/// Http client shared by every request, clones share connections and host limits
+#[derive(Debug, Clone)]
+pub struct Client {
+    inner: reqwest::Client,
+    config: Arc<ClientConfig>,
+    hosts: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
+}
+
+impl Default for Client {
+    fn default() -> Self {
+        Self::new(ClientConfig::default()).expect("Failed to build http client")
+    }
+}
+
+impl Client {
+    pub fn new(config: ClientConfig) -> Result<Self> {
+        let inner = reqwest::Client::builder()
+            .user_agent(&config.user_agent)
+            .timeout(config.timeout)
+            .build()
+            .map_err(|e|WikibaseError::Config(format!("Failed to build http client: {}", e)))?;
+
+        Ok(Self {
+            inner,
+            config: Arc::new(config),
+            hosts: Arc::default(),
+        })
+    }
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+    /// Body of the document behind `url`, retrying failures that might go away by themselves
+    pub async fn get(&self, url: &str) -> Result<Vec<u8>> {
+        let parsed = Url::parse(url).map_err(|e|WikibaseError::network(url, format!("Invalid url: {}", e)))?;
+        let host = parsed.host_str().unwrap_or_default().to_string();
+
+        // Hold a slot of the host for the whole duration of the request, retries included
+        let _permit = self.host(&host).acquire_owned().await.map_err(|e|WikibaseError::network(url, e))?;
+
+        let cache = self.config.cache.as_ref().map(Snapshots::new);
+        let validators = match &cache {
+            Some(c) => read_validators(c, url).await,
+            None => Validators::default(),
+        };
+
+        let mut attempt = 0;
+        let mut status: Option<u16>;
+
+        loop {
+            let request = conditional(self.inner.get(parsed.clone()), &validators);
+
+            let retry_in = match request.send().await {
+                Ok(r) if r.status() == StatusCode::NOT_MODIFIED => match &cache {
+                    Some(c) => {
+                        debug!("Using cached copy of {} as it's not modified", url);
+                        return c.read(url).await
+                    },
+                    None => return Err(WikibaseError::Network {
+                        url: url.to_string(),
+                        status: Some(r.status().as_u16()),
+                        reason: "Not modified without a cached copy".to_string(),
+                    }),
+                },
+                Ok(r) if r.status().is_success() => return self.store(cache.as_ref(), url, r).await,
+                Ok(r) if retryable(r.status()) => {
+                    warn!("GET {} failed with status {}", url, r.status());
+                    status = Some(r.status().as_u16());
+                    retry_after(&r)
+                },
+                Ok(r) => return Err(WikibaseError::Network {
+                    url: url.to_string(),
+                    status: Some(r.status().as_u16()),
+                    reason: format!("Response status {}", r.status()),
+                }),
+                Err(e) if e.is_timeout() || e.is_connect() || e.is_request() => {
+                    warn!("GET {} failed: {}", url, e);
+                    status = None;
+                    None
+                },
+                Err(e) => return Err(WikibaseError::network(url, e)),
+            };
+
+            if attempt >= self.config.retries {
+                return Err(WikibaseError::Network {
+                    url: url.to_string(),
+                    status,
+                    reason: format!("Failed after {} attempts", attempt + 1),
+                })
+            }
+
+            let delay = retry_in.unwrap_or_else(||self.backoff(attempt));
+            attempt += 1;
+
+            debug!("Retrying {} in {:?} ({}/{})", url, delay, attempt, self.config.retries);
+            sleep(delay).await;
+        }
+    }
+    fn host(&self, host: &str) -> Arc<Semaphore> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e|e.into_inner());
+
+        hosts.entry(host.to_string())
+            .or_insert_with(||Arc::new(Semaphore::new(self.config.per_host.max(1))))
+            .clone()
+    }
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.config.backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.config.max_backoff)
+    }
+    async fn store(&self, cache: Option<&Snapshots>, url: &str, resp: Response) -> Result<Vec<u8>> {
+        let validators = Validators {
+            etag: header(&resp, ETAG),
+            last_modified: header(&resp, LAST_MODIFIED),
+        };
+
+        let bytes = resp.bytes().await.map_err(|e|WikibaseError::network(url, e))?.to_vec();
+
+        if let Some(c) = cache {
+            if validators.etag.is_some() || validators.last_modified.is_some() {
+                c.write(url, &bytes).await?;
+                write_validators(c, url, &validators).await?;
+            }
+        }
+
+        Ok(bytes)
+    }
+}
+
+fn conditional(request: RequestBuilder, validators: &Validators) -> RequestBuilder {
+    let request = match &validators.etag {
+        Some(e) => request.header(IF_NONE_MATCH, e),
+        None => request,
+    };
+
+    match &validators.last_modified {
+        Some(m) => request.header(IF_MODIFIED_SINCE, m),
+        None => request,
+    }
+}
+
+fn retryable(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS || status == StatusCode::REQUEST_TIMEOUT
+}
+
+// Delay the server asked for, only the seconds form is supported
+fn retry_after(resp: &Response) -> Option<Duration> {
+    header(resp, RETRY_AFTER)
+        .and_then(|s|s.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+fn header(resp: &Response, name: reqwest::header::HeaderName) -> Option<String> {
+    resp.headers().get(name).and_then(|v|v.to_str().ok()).map(|s|s.to_string())
+}
+
+fn validators_path(cache: &Snapshots, url: &str) -> Result<PathBuf> {
+    let mut path = cache.path(url)?.into_os_string();
+    path.push(".validators.json");
+
+    Ok(path.into())
+}
+
+async fn read_validators(cache: &Snapshots, url: &str) -> Validators {
+    // Validators are useless without the cached body
+    let body = cache.path(url).is_ok_and(|p|p.is_file());
+
+    let path = match validators_path(cache, url) {
+        Ok(p) if body => p,
+        _ => return Validators::default(),
+    };
+
+    match read_to_string(&path).await {
+        Ok(d) => serde_json::from_str(&d).unwrap_or_default(),
+        Err(_) => Validators::default(),
+    }
+}
+
+async fn write_validators(cache: &Snapshots, url: &str, validators: &Validators) -> Result<()> {
+    let path = validators_path(cache, url)?;
+
+    let json = serde_json::to_string_pretty(validators).map_err(|e|WikibaseError::json("cache validators", e))?;
+
+    write(&path, json).await.map_err(|e|
+        WikibaseError::io(format!("Failed to write cache validators of {} to", url), &path, e)
+    )
+}
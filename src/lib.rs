@@ -0,0 +1,27 @@
+//! Country, currency, language and flag data collected from Wikipedia.
+//!
+//! The scraper binary generates the JSON files under `output`, this library exposes the
+//! serde models behind them and [`Dataset`] for loading a generated directory back in.
+
+pub mod coverage;
+pub mod diff;
+pub mod error;
+pub mod export;
+pub mod fetch;
+pub mod http;
+pub mod matcher;
+pub mod mediawiki;
+pub mod metadata;
+pub mod phone;
+pub mod pipeline;
+pub mod report;
+pub mod schema;
+pub mod snapshot;
+pub mod types;
+pub mod urls;
+mod dataset;
+mod map;
+
+pub use dataset::{Dataset, DatasetKind};
+pub use error::{ErrorKind, Result, WikibaseError};
+pub use types::Identifier;
@@ -1,12 +1,30 @@
 use std::collections::HashMap;
-use anyhow::{anyhow, bail, Result};
+use std::ops::Index;
+use regex::Regex;
 use scraper::{ElementRef, Html, Selector};
 use scraper::selectable::Selectable;
 use tracing::{debug, info, warn};
 
-pub enum Include {
-    All{ th_count: usize, td_map: HashMap<usize, Select> },
-    Some{ th_count: usize, td_map: HashMap<usize, Option<Select>>},
+use crate::error::{Result, WikibaseError};
+use crate::report::Report;
+use crate::DatasetKind;
+
+/// Columns to collect from a table, bound by the text of their header
+#[derive(Default)]
+pub struct Include {
+    columns: Vec<Column>,
+}
+
+struct Column {
+    // Key the cells of this column are found under
+    key: &'static str,
+    header: Header,
+    select: Select,
+}
+
+enum Header {
+    Name(&'static str),
+    Pattern(&'static str),
 }
 
 pub enum Select {
@@ -21,23 +39,117 @@ pub enum Found<'a> {
     Parent(ElementRef<'a>),
 }
 
-pub fn map_from_table_data<'a>(html: &'a Html, collect: Include, table_index_filter: Option<&[usize]>) -> Result<Vec<HashMap<usize, Found<'a>>>> {
+/// Cells collected from one table row, by column key
+pub struct Row<'a> {
+    /// Index of the table among the tables of the page
+    pub table: usize,
+    /// Index of the row among the data rows of the table
+    pub index: usize,
+    cells: HashMap<&'static str, Found<'a>>,
+}
+
+// Header of a table column, made of every header cell above it when the header spans several rows
+struct Title {
+    // Text of all the header cells from top to bottom, for example 'ISO 3166-1 A-2'
+    full: String,
+    // Text of the lowest header cell, for example 'A-2'
+    last: String,
+}
+
+// Header with its pattern compiled, bound to a column index once a table has it
+enum Matcher<'a> {
+    Name(&'a str),
+    Pattern(Regex),
+}
+
+impl Include {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Collect the column titled `name` (case and footnote markers ignored), found under the same key
+    pub fn column(mut self, name: &'static str, select: Select) -> Self {
+        self.columns.push(Column { key: name, header: Header::Name(name), select });
+        self
+    }
+    /// Collect the column whose title matches regular expression `pattern`, found under `key`
+    pub fn column_matching(mut self, key: &'static str, pattern: &'static str, select: Select) -> Self {
+        self.columns.push(Column { key, header: Header::Pattern(pattern), select });
+        self
+    }
+    fn keys(&self) -> String {
+        quoted(self.columns.iter().map(|c|c.key))
+    }
+}
+
+impl Found<'_> {
+    /// Text content of the cell, for error messages
+    pub fn text(&self) -> String {
+        let text = match self {
+            Self::Children(v) => v.iter().flat_map(|e|e.text()).collect::<String>(),
+            Self::InnerText(v) => v.concat(),
+            Self::Parent(e) => e.text().collect::<String>(),
+        };
+
+        text.split_whitespace().collect::<Vec<&str>>().join(" ")
+    }
+}
+
+impl<'a> Row<'a> {
+    pub fn get(&self, key: &str) -> Option<&Found<'a>> {
+        self.cells.get(key)
+    }
+}
+
+impl<'a> Index<&str> for Row<'a> {
+    type Output = Found<'a>;
+
+    fn index(&self, key: &str) -> &Found<'a> {
+        &self.cells[key]
+    }
+}
+
+impl Matcher<'_> {
+    fn matches(&self, title: &Title) -> bool {
+        match self {
+            Self::Name(n) => title.full.eq_ignore_ascii_case(n) || title.last.eq_ignore_ascii_case(n),
+            Self::Pattern(r) => r.is_match(&title.full) || r.is_match(&title.last),
+        }
+    }
+}
+
+pub fn map_from_table_data<'a>(
+    html: &'a Html,
+    dataset: DatasetKind,
+    collect: Include,
+    table_index_filter: Option<&[usize]>,
+    report: &mut Report,
+) -> Result<Vec<Row<'a>>> {
     // Search for tables in html document
     let document = html.root_element();
     let table_sel = Selector::parse("table").unwrap();
     let doc_table_els = document.select(&table_sel).collect::<Vec<ElementRef<'_>>>();
 
     if doc_table_els.is_empty() {
-        bail!("Provided html document does not contain any tables");
+        return Err(WikibaseError::layout(dataset, "Provided html document does not contain any tables"))
     }
 
-    // We're only interested in TD elements, but we need to be aware of the columns count
-    // so read TR elements first and see if TD element count inside matches column collect arg
+    let matchers = collect.columns.iter()
+        .map(|c|match c.header {
+            Header::Name(n) => Ok(Matcher::Name(n)),
+            Header::Pattern(p) => Regex::new(p).map(Matcher::Pattern).map_err(|e|
+                WikibaseError::layout(dataset, format!("Failed to parse column pattern '{}': {}", p, e))
+            ),
+        })
+        .collect::<Result<Vec<Matcher>>>()?;
+
     let tr_sel = Selector::parse("tr").unwrap();
     let td_sel = Selector::parse("td").unwrap();
-    let th_sel = Selector::parse("th").unwrap();
-    
-    // Loop through tables, match 
+
+    // Table that had the most of the columns but not all, to tell what is missing if no table has them all
+    let mut closest: Option<(usize, Vec<&'static str>)> = None;
+    let mut tables = 0;
+
+    // Loop through tables, match
     let mut elements = Vec::new();
 
     for (table_i, table_el) in doc_table_els.iter().enumerate() {
@@ -50,85 +162,202 @@ pub fn map_from_table_data<'a>(html: &'a Html, collect: Include, table_index_fil
             }
         }
 
-        // Detect tables by their TH element count
-        let req_len = match &collect {
-            Include::All{ th_count, ..} => th_count,
-            Include::Some{ th_count, ..} => th_count,
-        };
+        // Header rows are the ones before the first row with TD elements
+        let table_tr_els = table_el.select(&tr_sel).collect::<Vec<ElementRef<'_>>>();
+        let head_len = table_tr_els.iter()
+            .position(|e|e.select(&td_sel).next().is_some())
+            .unwrap_or(table_tr_els.len());
+
+        // Cells spanning several rows or columns are found in every slot they cover
+        let grid = grid(&table_tr_els);
+        let titles = titles(&grid[..head_len]);
+
+        // Bind each requested column to the first column with a matching title
+        let mut bound = Vec::new();
+        let mut missing = Vec::new();
+
+        for (column, matcher) in collect.columns.iter().zip(&matchers) {
+            match titles.iter().position(|t|matcher.matches(t)) {
+                Some(i) => bound.push((i, column)),
+                None => missing.push(column.key),
+            }
+        }
+
+        if !missing.is_empty() {
+            match bound.is_empty() {
+                true => debug!("Skipping table number {} as it has none of the columns {}", table_i, collect.keys()),
+                false => warn!("Skipping table number {} as it's missing columns {}", table_i, quoted(missing.iter().copied())),
+            }
+
+            if closest.as_ref().is_none_or(|(_, m)|missing.len() < m.len()) {
+                closest = Some((table_i, missing));
+            }
 
-        // Study table head to see if column count is what we're after
-        if table_el.select(&th_sel).count().ne(req_len) {
-            warn!("Skipping table number {} as it has {} columns, but {} are required", table_i, table_el.select(&th_sel).count(), req_len);
             continue;
         }
 
-        // Rows in table, collect the ones with appropriate number of TD elements
-        let table_tr_els = match &collect {
-            Include::All{ td_map, ..} => table_el.select(&tr_sel)
-                .filter(|e|e.select(&td_sel).count() == td_map.len())
-                .collect::<Vec<ElementRef<'_>>>(),
-            Include::Some{ td_map, ..} => {
-                let rl = td_map.iter().filter(|(_, v)|v.is_some()).count();
-
-                table_el.select(&tr_sel)
-                    .filter(|e|e.select(&td_sel).count() >= rl)
-                    .collect::<Vec<ElementRef<'_>>>()
+        tables += 1;
+
+        // Loop rows applying the provided selector to each bound cell
+        for (row_i, cells) in grid[head_len..].iter().enumerate() {
+            let cells = match bound.iter().map(|(i, _)|cells.get(*i).copied().flatten()).collect::<Option<Vec<ElementRef<'_>>>>() {
+                Some(c) => c,
+                None => {
+                    report.skip(table_i, row_i, row_text(cells), "Row has no cells for some of the columns");
+                    continue;
                 }
-        };
+            };
+
+            // Single cell spanning the whole row separates groups of rows, it has no data of its own
+            if titles.len() > 1 && cells.windows(2).all(|w|w[0] == w[1]) && span(cells[0], "colspan") >= titles.len() {
+                debug!("Skipping row {} of table {} as it's a single cell spanning all columns", row_i, table_i);
+                continue;
+            }
 
-        // Loop rows applying the provided selector to each cell or ignoring excluded columns
-        for table_row in table_tr_els {
             let mut scraped = HashMap::new();
-            let table_row_td_els = table_row.select(&td_sel).collect::<Vec<ElementRef<'_>>>();
-
-            for (td_index, td_el) in table_row_td_els.into_iter().enumerate() {
-                // See if this element is to be collected, and if so what's the rule
-                let rule = match &collect {
-                    Include::All{ td_map, ..} => match td_map.get(&td_index) {
-                        Some(s) => s,
-                        None => panic!("Stupid developer error: All columns set to be collected but selector for {} is undefined", td_index),
-                    },
-                    Include::Some{ td_map, ..} => match td_map.get(&td_index) {
-                        Some(o) => match o {
-                            Some(s) => s,
-                            None => continue,
-                        },
-                        None => {
-                            debug!("Limited columns set to be collected but selector for {} is undefined (Ignored column)", td_index);
-                            continue;
-                        },
-                    },
-                };
 
-                match rule {
+            for ((_, column), td_el) in bound.iter().zip(cells) {
+
+                match column.select {
                     Select::Matching(s) => {
                         let sel = Selector::parse(s).map_err(|e|
-                            anyhow!("Failed to parse selector from '{}': {}", s, e)
+                            WikibaseError::layout(dataset, format!("Failed to parse selector from '{}': {}", s, e))
                         )?;
 
                         // Finds all elements matching the selector and stores them in the result map
                         let els = td_el.select(&sel).collect::<Vec<ElementRef<'_>>>();
 
-                        scraped.insert(td_index, Found::Children(els));
+                        scraped.insert(column.key, Found::Children(els));
                     },
                     Select::InnerAsText => {
                         let els = td_el.text()
                             .map(|t|t.to_string())
                             .collect::<Vec<String>>();
 
-                        scraped.insert(td_index, Found::InnerText(els));
+                        scraped.insert(column.key, Found::InnerText(els));
                     },
                     Select::TdElement => {
-                        scraped.insert(td_index, Found::Parent(td_el));
+                        scraped.insert(column.key, Found::Parent(td_el));
                     }
                 }
+            }
+
+            elements.push(Row { table: table_i, index: row_i, cells: scraped });
+        }
+    }
+
+    if tables == 0 {
+        let reason = match closest {
+            Some((i, m)) => format!("Table number {} is missing columns {}", i, quoted(m.into_iter())),
+            None => format!("No table has columns {}", collect.keys()),
+        };
+
+        return Err(WikibaseError::layout(dataset, reason))
+    }
+
+    Ok(elements)
+}
+
+// Cells of a table row, not the ones of tables nested in them
+fn cells(row: ElementRef<'_>) -> Vec<ElementRef<'_>> {
+    row.children()
+        .filter_map(ElementRef::wrap)
+        .filter(|e|matches!(e.value().name(), "td" | "th"))
+        .collect()
+}
+
+fn span(cell: ElementRef<'_>, attr: &str) -> usize {
+    cell.value().attr(attr)
+        .and_then(|s|s.trim().parse::<usize>().ok())
+        .unwrap_or(1)
+        .max(1)
+}
+
+// Slots of every row of a table, a cell spanning several columns or rows is found in all the slots it covers
+fn grid<'a>(rows: &[ElementRef<'a>]) -> Vec<Vec<Option<ElementRef<'a>>>> {
+    let mut grid: Vec<Vec<Option<ElementRef<'a>>>> = vec![Vec::new(); rows.len()];
+
+    for (row_i, row) in rows.iter().enumerate() {
+        let mut col = 0;
+
+        for cell in cells(*row) {
+            // Skip slots taken by cells spanning down from the rows above
+            while grid[row_i].get(col).is_some_and(|c|c.is_some()) {
+                col += 1;
+            }
+
+            // Span of 0 covers the rest of the table section, same as spanning to the last row
+            let rowspan = match cell.value().attr("rowspan").map(|s|s.trim()) {
+                Some("0") => rows.len() - row_i,
+                _ => span(cell, "rowspan").min(rows.len() - row_i),
+            };
+            let colspan = span(cell, "colspan");
+
+            for r in &mut grid[row_i..row_i + rowspan] {
+                if r.len() < col + colspan {
+                    r.resize(col + colspan, None);
+                }
+
+                for slot in &mut r[col..col + colspan] {
+                    *slot = Some(cell);
+                }
+            }
+
+            col += colspan;
+        }
+    }
+
+    grid
+}
+
+// Title of each column from the header rows of the grid
+fn titles(grid: &[Vec<Option<ElementRef<'_>>>]) -> Vec<Title> {
+    let width = grid.iter().map(|r|r.len()).max().unwrap_or(0);
+    let footnotes = Regex::new(r"\[[^\]]*\]").unwrap();
+
+    (0..width)
+        .map(|col| {
+            let mut above: Vec<ElementRef<'_>> = Vec::new();
+
+            for cell in grid.iter().filter_map(|r|r.get(col).copied().flatten()) {
+                if above.last() != Some(&cell) {
+                    above.push(cell);
+                }
+            }
 
-                
+            let texts = above.into_iter().map(|c|title_text(c, &footnotes)).collect::<Vec<String>>();
+
+            Title {
+                full: texts.join(" ").trim().to_string(),
+                last: texts.last().cloned().unwrap_or_default(),
             }
+        })
+        .collect()
+}
+
+// Text of every cell of a grid row once, for reports
+fn row_text(cells: &[Option<ElementRef<'_>>]) -> String {
+    let mut seen: Vec<ElementRef<'_>> = Vec::new();
 
-            elements.push(scraped);
+    for cell in cells.iter().flatten() {
+        if !seen.contains(cell) {
+            seen.push(*cell);
         }
     }
-    
-    return Ok(elements)
+
+    seen.into_iter()
+        .map(|e|e.text().collect::<String>().split_whitespace().collect::<Vec<&str>>().join(" "))
+        .collect::<Vec<String>>()
+        .join(" | ")
+}
+
+// Header cell text without footnote markers such as '[1]' or '[c]'
+fn title_text(cell: ElementRef<'_>, footnotes: &Regex) -> String {
+    let text = cell.text().collect::<String>();
+
+    footnotes.replace_all(&text, "").split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+fn quoted<'a, I: Iterator<Item = &'a str>>(keys: I) -> String {
+    keys.map(|k|format!("'{}'", k)).collect::<Vec<String>>().join(", ")
 }
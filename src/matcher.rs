@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use crate::Identifier;
+
+
+/// Known name found in a text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameMatch {
+    pub id: Identifier,
+    /// Phrase of the text that matched, as it was written
+    pub phrase: String,
+    /// Byte offsets of the phrase in the text
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: BTreeMap<String, Node>,
+    /// Identifier of the name ending here
+    id: Option<Identifier>,
+}
+
+/// Names of several words compiled to a trie of their words, texts are searched for the longest names they contain
+#[derive(Debug, Clone, Default)]
+pub struct NameMatcher {
+    root: Node,
+}
+
+impl NameMatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds a name of `id`, the first identifier given a name keeps it
+    pub fn insert(&mut self, name: &str, id: &Identifier) {
+        let words = words(name);
+
+        if words.is_empty() {
+            return
+        }
+
+        let mut node = &mut self.root;
+
+        for (start, end) in words {
+            node = node.children.entry(name[start..end].to_lowercase()).or_default();
+        }
+
+        node.id.get_or_insert_with(||id.clone());
+    }
+    /// Known names in the text from left to right, a name within a longer one is not matched on its own
+    pub fn find(&self, text: &str) -> Vec<NameMatch> {
+        let words = words(text);
+        let lower = words.iter().map(|(s, e)|text[*s..*e].to_lowercase()).collect::<Vec<String>>();
+
+        let mut matches = Vec::new();
+        let mut i = 0;
+
+        while i < words.len() {
+            let mut node = &self.root;
+            // Identifier of the longest name starting from this word and the index of its last word
+            let mut longest = None;
+
+            for (j, w) in lower.iter().enumerate().skip(i) {
+                node = match node.children.get(w) {
+                    Some(n) => n,
+                    None => break,
+                };
+
+                if let Some(id) = &node.id {
+                    longest = Some((id, j));
+                }
+            }
+
+            match longest {
+                Some((id, j)) => {
+                    let (start, end) = (words[i].0, words[j].1);
+
+                    matches.push(NameMatch { id: id.clone(), phrase: text[start..end].to_string(), start, end });
+                    i = j + 1;
+                },
+                None => i += 1,
+            }
+        }
+
+        matches
+    }
+}
+
+// Byte offsets of the words in the text, anything but letters and digits separates words
+fn words(text: &str) -> Vec<(usize, usize)> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    for (i, c) in text.char_indices() {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                words.push((s, i));
+                start = None;
+            },
+            _ => (),
+        }
+    }
+
+    if let Some(s) = start {
+        words.push((s, text.len()));
+    }
+
+    words
+}
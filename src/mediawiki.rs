@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::error::{Result, WikibaseError};
+use crate::fetch::Source;
+
+
+/// Requests parsed article html from the MediaWiki Parse API instead of scraping rendered pages
+#[derive(Debug, Clone, Default)]
+pub struct MediaWiki {
+    // Api url used for every wiki, by default each wiki's own `/w/api.php`
+    endpoint: Option<String>,
+    // Revisions pages are pinned to, by page url
+    pins: BTreeMap<String, u64>,
+}
+
+/// Parsed html of a page at some revision
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub html: String,
+    pub revision: u64,
+}
+
+#[derive(Deserialize)]
+struct Response {
+    parse: Option<Parse>,
+    error: Option<ApiError>,
+}
+
+#[derive(Deserialize)]
+struct Parse {
+    revid: u64,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    code: String,
+    info: String,
+}
+
+impl MediaWiki {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Use given api url instead of the one of each wiki
+    pub fn endpoint<T: Into<String>>(mut self, url: T) -> Self {
+        self.endpoint = Some(url.into());
+        self
+    }
+    /// Always request the page behind `url` at given revision
+    pub fn pin<T: Into<String>>(mut self, url: T, revision: u64) -> Self {
+        self.pins.insert(url.into(), revision);
+        self
+    }
+    /// Pin pages to the revisions they were read at, for example sources of a previous build
+    pub fn pin_sources<'a, I>(mut self, sources: I) -> Self where I: IntoIterator<Item = &'a Source> {
+        for s in sources {
+            if let Some(r) = s.revision {
+                self.pins.insert(s.url.clone(), r);
+            }
+        }
+
+        self
+    }
+    pub fn pins(&self) -> &BTreeMap<String, u64> {
+        &self.pins
+    }
+    /// Api url for the parsed html of a wiki article, `None` if `url` is not an article
+    pub fn request(&self, url: &str) -> Option<String> {
+        let (base, title) = url.split_once("/wiki/")?;
+
+        if title.is_empty() || !base.contains("://") {
+            return None
+        }
+
+        let endpoint = match &self.endpoint {
+            Some(e) => e.clone(),
+            None => format!("{}/w/api.php", base),
+        };
+
+        let page = match self.pins.get(url) {
+            Some(r) => format!("oldid={}", r),
+            None => format!("page={}&redirects=1", title),
+        };
+
+        Some(format!("{}?action=parse&format=json&formatversion=2&prop=text%7Crevid&{}", endpoint, page))
+    }
+    /// Reads the html and revision id from the Parse API response for the article behind `url`
+    pub fn parse(url: &str, body: &[u8]) -> Result<Page> {
+        let resp = serde_json::from_slice::<Response>(body).map_err(|e|
+            WikibaseError::network(url, format!("Invalid MediaWiki API response: {}", e))
+        )?;
+
+        match (resp.parse, resp.error) {
+            (_, Some(e)) => Err(WikibaseError::network(url, format!("MediaWiki API error {}: {}", e.code, e.info))),
+            (Some(p), None) => Ok(Page { html: p.text, revision: p.revid }),
+            (None, None) => Err(WikibaseError::network(url, "MediaWiki API response has no parsed page")),
+        }
+    }
+}
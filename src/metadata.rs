@@ -0,0 +1,97 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+use tokio::fs::{read_to_string, write};
+use tracing::{debug, warn};
+
+use crate::error::{Result, WikibaseError};
+use crate::fetch::Source;
+use crate::DatasetKind;
+
+/// Name of the file metadata is stored in, next to the data sets
+pub const METADATA_FILE: &str = "metadata.json";
+
+
+/// When and from where a data set was fetched
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DatasetMeta {
+    #[serde(with = "timestamp")]
+    pub fetched: SystemTime,
+    pub sources: Vec<Source>,
+    /// Sha256 of the data set JSON
+    pub hash: String,
+    /// Fingerprint of the data sets this one was built from
+    pub inputs: String,
+}
+
+impl DatasetMeta {
+    pub fn age(&self) -> Duration {
+        SystemTime::now().duration_since(self.fetched).unwrap_or_default()
+    }
+}
+
+/// Metadata of every data set in an output directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Metadata {
+    pub datasets: BTreeMap<DatasetKind, DatasetMeta>,
+}
+
+impl Metadata {
+    /// Reads metadata from given directory, missing or invalid metadata is treated as empty
+    pub async fn read<P: AsRef<Path>>(dir: P) -> Self {
+        let path = dir.as_ref().join(METADATA_FILE);
+
+        if !path.exists() {
+            debug!("No previous metadata in {}", path.to_string_lossy());
+            return Self::default()
+        }
+
+        Self::from_file(&path).await.unwrap_or_else(|e| {
+            warn!("Ignoring previous metadata: {}", e);
+            Self::default()
+        })
+    }
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = read_to_string(path).await.map_err(|e|
+            WikibaseError::io("Failed to read metadata from", path, e)
+        )?;
+
+        serde_json::from_str(&json).map_err(|e|
+            WikibaseError::json(format!("metadata {}", path.to_string_lossy()), e)
+        )
+    }
+    pub async fn write<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let path = dir.as_ref().join(METADATA_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(|e|WikibaseError::json("metadata", e))?;
+
+        write(&path, json).await.map_err(|e|
+            WikibaseError::io("Failed to write metadata to", &path, e)
+        )
+    }
+    pub fn get(&self, kind: DatasetKind) -> Option<&DatasetMeta> {
+        self.datasets.get(&kind)
+    }
+    /// Every document the data sets were read from
+    pub fn sources(&self) -> impl Iterator<Item = &Source> {
+        self.datasets.values().flat_map(|m|m.sources.iter())
+    }
+}
+
+// Timestamps are stored as RFC 3339 with second precision
+mod timestamp {
+    use std::time::SystemTime;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&humantime::format_rfc3339_seconds(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        humantime::parse_rfc3339(&s).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Result, WikibaseError};
+use crate::types::CallingCode;
+use crate::{DatasetKind, Identifier};
+
+/// Country code of the North American Numbering Plan, shared by the United States, Canada and most of the Caribbean
+pub const NANP: u16 = 1;
+
+// Longest number E.164 allows, country code included
+const MAX_DIGITS: usize = 15;
+
+
+/// Phone number split to its country code and national number, with the regions it might belong to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhoneNumber {
+    /// Region of the number, none when there's more than one candidate
+    pub region: Option<Identifier>,
+    pub country_code: u16,
+    /// Digits following the country code, area code included
+    pub national_number: String,
+    /// Regions the number might belong to
+    pub candidates: Vec<Identifier>,
+}
+
+impl PhoneNumber {
+    /// More than one region might own the number, such as a `+1` number without an area code of its own
+    pub fn is_ambiguous(&self) -> bool {
+        self.candidates.len() > 1
+    }
+    pub fn is_nanp(&self) -> bool {
+        self.country_code == NANP
+    }
+    /// Number in E.164 format, `+358401234567`
+    pub fn e164(&self) -> String {
+        format!("+{}{}", self.country_code, self.national_number)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    children: BTreeMap<char, Node>,
+    /// Regions with a prefix ending here
+    regions: Vec<Identifier>,
+    /// Country code ending here
+    country_code: Option<u16>,
+}
+
+impl Node {
+    // Regions of this node and every node below it
+    fn all_regions(&self, regions: &mut Vec<Identifier>) {
+        regions.extend(self.regions.iter().cloned());
+        self.children.values().for_each(|c|c.all_regions(regions));
+    }
+}
+
+/// Calling codes of every region compiled to a trie of their prefixes, numbers are classified by the longest matching one
+#[derive(Debug, Clone, Default)]
+pub struct PrefixTrie {
+    root: Node,
+}
+
+impl PrefixTrie {
+    pub fn new(codes: &BTreeMap<Identifier, CallingCode>) -> Self {
+        let mut root = Node::default();
+
+        for (id, code) in codes {
+            let country_code = code.country_code.to_string();
+
+            for prefix in code.prefixes() {
+                let mut node = &mut root;
+
+                for (i, d) in prefix.chars().enumerate() {
+                    node = node.children.entry(d).or_default();
+
+                    if i + 1 == country_code.len() {
+                        node.country_code = Some(code.country_code);
+                    }
+                }
+
+                if !node.regions.contains(id) {
+                    node.regions.push(id.clone());
+                }
+            }
+        }
+
+        Self { root }
+    }
+    /// Normalizes a phone number and finds the regions it belongs to
+    pub fn classify(&self, number: &str) -> Result<PhoneNumber> {
+        let digits = normalize(number).ok_or_else(||invalid(format!("'{}' is not a phone number", number)))?;
+
+        let mut node = &self.root;
+        // Country code with the number of digits it takes, and the deepest node with regions
+        let mut country_code = None;
+        let mut deepest = None;
+        let mut exhausted = true;
+
+        for (i, d) in digits.chars().enumerate() {
+            node = match node.children.get(&d) {
+                Some(n) => n,
+                None => {
+                    exhausted = false;
+                    break
+                },
+            };
+
+            if let Some(c) = node.country_code {
+                country_code = Some((c, i + 1));
+            }
+            if !node.regions.is_empty() {
+                deepest = Some(node);
+            }
+        }
+
+        let (country_code, length) = country_code.ok_or_else(||invalid(format!("No calling code matches '{}'", number)))?;
+
+        let mut candidates = deepest.map(|n|n.regions.clone()).unwrap_or_default();
+
+        // Number ends before its area code does, any region further down might own it
+        if exhausted {
+            node.children.values().for_each(|c|c.all_regions(&mut candidates));
+        }
+
+        candidates.sort();
+        candidates.dedup();
+
+        Ok(PhoneNumber {
+            region: match candidates.len() {
+                1 => candidates.first().cloned(),
+                _ => None,
+            },
+            country_code,
+            national_number: digits[length..].to_string(),
+            candidates,
+        })
+    }
+}
+
+// Numbers are checked against the calling codes data set
+fn invalid(reason: String) -> WikibaseError {
+    WikibaseError::validation(DatasetKind::CallingCodes, reason)
+}
+
+/// Digits of an international phone number without the `+` or `00` prefix, spaces and punctuation
+pub fn normalize(number: &str) -> Option<String> {
+    let number = number.chars()
+        .filter(|c|!c.is_whitespace() && !matches!(c, '-' | '.' | '(' | ')' | '/'))
+        .collect::<String>();
+
+    let digits = number.strip_prefix('+')
+        .or_else(||number.strip_prefix("00"))
+        .unwrap_or(&number);
+
+    match !digits.is_empty() && digits.len() <= MAX_DIGITS && digits.chars().all(|c|c.is_ascii_digit()) {
+        true => Some(digits.to_string()),
+        false => None,
+    }
+}
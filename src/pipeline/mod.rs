@@ -0,0 +1,591 @@
+mod stages;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::fmt::Result as Formatted;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use sha2::{Digest, Sha256};
+use tokio::fs::{create_dir_all, metadata, read_to_string, write};
+use tracing::{error, info, warn};
+
+use crate::diff::snapshot;
+use crate::error::{ErrorKind, Result, WikibaseError};
+use crate::fetch::Fetcher;
+use crate::metadata::{DatasetMeta, Metadata};
+use crate::report::{Report, Reports};
+#[cfg(feature = "validate")]
+use crate::schema::validate;
+use crate::schema::write_schemas;
+use crate::types::{CallingCode, Capital, Country, Currency, Flag, Identifier, Language, LanguageRegion, Region, SovereignState, UNMember};
+use crate::{Dataset, DatasetKind};
+
+pub use stages::{
+    CallingCodesStage, CapitalsStage, CountriesStage, CurrenciesStage, EmojisStage, FlagsStage, LanguagesStage,
+    RegionsStage, SovereignStatesStage, UnNationsStage
+};
+
+
+/// Settings shared by every stage of a pipeline run
+#[derive(Debug, Clone)]
+pub struct Context {
+    pub fetcher: Fetcher,
+    pub output: PathBuf,
+    /// Map of ISO 3166 codes to country names used to identify countries
+    pub countries: BTreeMap<Identifier, Vec<String>>,
+    /// Data sets requested to be built, stages they depend on are run as well
+    pub selected: BTreeSet<DatasetKind>,
+    /// Fetch selected data sets again even if previous results exist
+    pub force: bool,
+    /// Fetch any needed data set again if previous results are older than this
+    pub max_age: Option<Duration>,
+    /// Directory of the SIL code tables of ISO 639-3, languages cover only ISO 639-1 without it
+    pub iso_639_3: Option<PathBuf>,
+}
+
+impl Context {
+    pub fn refetch(&self, kind: DatasetKind) -> bool {
+        self.force && self.selected.contains(&kind)
+    }
+}
+
+/// One data set in the pipeline: what it needs and how it's built
+#[async_trait(?Send)]
+pub trait Stage {
+    fn kind(&self) -> DatasetKind;
+    /// Data sets that have to be available before this stage can run
+    fn dependencies(&self) -> &'static [DatasetKind];
+    /// Whether stages depending on this one can proceed without its data if it fails
+    fn required(&self) -> bool {
+        true
+    }
+    /// Builds the data set along with a report of the rows that were left out
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)>;
+    /// Inputs other than data sets that identify what the stage builds, such as local files it reads, as names and values
+    async fn inputs(&self, _ctx: &Context) -> Vec<(String, String)> {
+        Vec::new()
+    }
+    /// Called with the data set in place, whether it was just built or read from previous results
+    async fn finish(&self, _ctx: &Context, _data: &Dataset) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Data produced by a stage
+#[derive(Debug, Clone)]
+pub enum Output {
+    UnNations(Vec<UNMember>),
+    SovereignStates(BTreeMap<Identifier, SovereignState>),
+    Regions(BTreeMap<Identifier, Region>),
+    Flags(BTreeMap<Identifier, Flag>),
+    Emojis(BTreeMap<Identifier, String>),
+    Currencies(BTreeMap<Identifier, Currency>),
+    CallingCodes(BTreeMap<Identifier, CallingCode>),
+    Languages(BTreeMap<Identifier, Language>),
+    Capitals(BTreeMap<Identifier, Capital>),
+    Countries(BTreeMap<Identifier, Country>),
+}
+
+impl Output {
+    pub fn kind(&self) -> DatasetKind {
+        match self {
+            Self::UnNations(_) => DatasetKind::UnNations,
+            Self::SovereignStates(_) => DatasetKind::SovereignStates,
+            Self::Regions(_) => DatasetKind::Regions,
+            Self::Flags(_) => DatasetKind::Flags,
+            Self::Emojis(_) => DatasetKind::Emojis,
+            Self::Currencies(_) => DatasetKind::Currencies,
+            Self::CallingCodes(_) => DatasetKind::CallingCodes,
+            Self::Languages(_) => DatasetKind::Languages,
+            Self::Capitals(_) => DatasetKind::Capitals,
+            Self::Countries(_) => DatasetKind::Countries,
+        }
+    }
+    pub fn len(&self) -> usize {
+        match self {
+            Self::UnNations(d) => d.len(),
+            Self::SovereignStates(d) => d.len(),
+            Self::Regions(d) => d.len(),
+            Self::Flags(d) => d.len(),
+            Self::Emojis(d) => d.len(),
+            Self::Currencies(d) => d.len(),
+            Self::CallingCodes(d) => d.len(),
+            Self::Languages(d) => d.len(),
+            Self::Capitals(d) => d.len(),
+            Self::Countries(d) => d.len(),
+        }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    pub fn to_json(&self) -> Result<String> {
+        let json = match self {
+            Self::UnNations(d) => serde_json::to_string_pretty(d),
+            Self::SovereignStates(d) => serde_json::to_string_pretty(d),
+            Self::Regions(d) => serde_json::to_string_pretty(d),
+            Self::Flags(d) => serde_json::to_string_pretty(d),
+            Self::Emojis(d) => serde_json::to_string_pretty(d),
+            Self::Currencies(d) => serde_json::to_string_pretty(d),
+            Self::CallingCodes(d) => serde_json::to_string_pretty(d),
+            Self::Languages(d) => serde_json::to_string_pretty(d),
+            Self::Capitals(d) => serde_json::to_string_pretty(d),
+            Self::Countries(d) => serde_json::to_string_pretty(d),
+        };
+
+        json.map_err(|e|WikibaseError::json(format!("{} data", self.kind()), e))
+    }
+    pub fn from_json(kind: DatasetKind, json: &str) -> Result<Self> {
+        let output = match kind {
+            DatasetKind::UnNations => serde_json::from_str(json).map(Self::UnNations),
+            DatasetKind::SovereignStates => serde_json::from_str(json).map(Self::SovereignStates),
+            DatasetKind::Regions => serde_json::from_str(json).map(Self::Regions),
+            DatasetKind::Flags => serde_json::from_str(json).map(Self::Flags),
+            DatasetKind::Emojis => serde_json::from_str(json).map(Self::Emojis),
+            DatasetKind::Currencies => serde_json::from_str(json).map(Self::Currencies),
+            DatasetKind::CallingCodes => serde_json::from_str(json).map(Self::CallingCodes),
+            DatasetKind::Languages => serde_json::from_str(json).map(Self::Languages),
+            DatasetKind::Capitals => serde_json::from_str(json).map(Self::Capitals),
+            DatasetKind::Countries => serde_json::from_str(json).map(Self::Countries),
+        };
+
+        output.map_err(|e|WikibaseError::json(format!("{} data", kind), e))
+    }
+    /// Sorts the lists that are sets of values and drops duplicates from them, rows moving around in a page don't change the output
+    pub fn normalize(&mut self) {
+        match self {
+            Self::Currencies(d) => d.values_mut().for_each(|c|sort_set(&mut c.regions)),
+            Self::Languages(d) => d.values_mut().for_each(|l|sort_regions(&mut l.regions)),
+            Self::Capitals(d) => d.values_mut().for_each(dedup_endonyms),
+            Self::Countries(d) => d.values_mut().for_each(|c|c.capital.iter_mut().for_each(dedup_endonyms)),
+            _ => (),
+        }
+    }
+    /// Moves the data in place of the corresponding data set
+    pub fn apply(self, data: &mut Dataset) {
+        match self {
+            Self::UnNations(d) => data.un_nations = d,
+            Self::SovereignStates(d) => data.sovereign_states = d,
+            Self::Regions(d) => data.regions = d,
+            Self::Flags(d) => data.flags = d,
+            Self::Emojis(d) => data.emojis = d,
+            Self::Currencies(d) => data.currencies = d,
+            Self::CallingCodes(d) => data.calling_codes = d,
+            Self::Languages(d) => data.languages = d,
+            Self::Capitals(d) => data.capitals = d,
+            Self::Countries(d) => data.countries = d,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// Fetched and parsed during this run
+    Built,
+    /// Read from previous results as its inputs haven't changed
+    Cached,
+    Failed(ErrorKind, String),
+    /// Not run as a stage it depends on failed
+    Skipped(String),
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        match self {
+            Self::Built => write!(f, "built"),
+            Self::Cached => write!(f, "cached"),
+            Self::Failed(_, e) => write!(f, "failed: {}", e),
+            Self::Skipped(e) => write!(f, "skipped: {}", e),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StageSummary {
+    pub kind: DatasetKind,
+    pub required: bool,
+    pub status: Status,
+    pub items: usize,
+    /// Table rows left out of the data set
+    pub skipped: usize,
+    pub elapsed: Duration,
+}
+
+/// Outcome of every stage that was part of a pipeline run, in the order they finished
+#[derive(Debug, Clone, Default)]
+pub struct Summary {
+    pub stages: Vec<StageSummary>,
+    /// Rows every data set left out, as written to `report.json`
+    pub reports: Reports,
+}
+
+impl Summary {
+    /// Whether any stage other data sets can't do without failed or was skipped
+    pub fn failed(&self) -> bool {
+        self.stages.iter().any(|s|s.required && matches!(s.status, Status::Failed(..) | Status::Skipped(_)))
+    }
+    /// Kind of the first failure of a stage other data sets can't do without
+    pub fn error(&self) -> Option<ErrorKind> {
+        self.stages.iter().find_map(|s|match (&s.status, s.required) {
+            (Status::Failed(k, _), true) => Some(*k),
+            _ => None,
+        })
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        writeln!(f, "{:<18} {:>7} {:>7} {:>9}  status", "stage", "items", "skipped", "time")?;
+
+        for s in &self.stages {
+            writeln!(f, "{:<18} {:>7} {:>7} {:>8.2}s  {}", s.kind.name(), s.items, s.skipped, s.elapsed.as_secs_f32(), s.status)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Result of a stage along with its metadata
+struct Run {
+    output: Output,
+    status: Status,
+    meta: DatasetMeta,
+    report: Report,
+}
+
+/// Stages and the order they're allowed to run in
+pub struct Pipeline {
+    stages: Vec<Box<dyn Stage>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Self::new(vec![
+            Box::new(UnNationsStage),
+            Box::new(SovereignStatesStage),
+            Box::new(RegionsStage),
+            Box::new(FlagsStage),
+            Box::new(EmojisStage),
+            Box::new(CurrenciesStage),
+            Box::new(CallingCodesStage),
+            Box::new(LanguagesStage),
+            Box::new(CapitalsStage),
+            Box::new(CountriesStage),
+        ])
+    }
+}
+
+impl Pipeline {
+    pub fn new(stages: Vec<Box<dyn Stage>>) -> Self {
+        Self { stages }
+    }
+    fn stage(&self, kind: DatasetKind) -> Result<&dyn Stage> {
+        self.stages.iter()
+            .find(|s|s.kind() == kind)
+            .map(|s|s.as_ref())
+            .ok_or_else(||WikibaseError::Config(format!("Pipeline has no stage for {}", kind)))
+    }
+    /// Selected data sets and everything they depend on
+    pub fn needed(&self, selected: &BTreeSet<DatasetKind>) -> Result<BTreeSet<DatasetKind>> {
+        let mut needed = BTreeSet::new();
+        let mut queue = selected.iter().copied().collect::<Vec<DatasetKind>>();
+
+        while let Some(k) = queue.pop() {
+            if needed.insert(k) {
+                queue.extend(self.stage(k)?.dependencies());
+            }
+        }
+
+        Ok(needed)
+    }
+    /// Runs every needed stage as soon as the stages it depends on are done, stages that are ready at the same time run concurrently
+    pub async fn run(&self, ctx: &Context) -> Result<(Dataset, Summary)> {
+        create_dir_all(&ctx.output).await.map_err(|e|
+            WikibaseError::io("Failed to create output directory", &ctx.output, e)
+        )?;
+
+        let needed = self.needed(&ctx.selected)?;
+
+        // Consumers of the data sets can check them against these
+        write_schemas(&ctx.output, needed.iter().copied()).await?;
+
+        let mut pending = needed.into_iter()
+            .map(|k|self.stage(k))
+            .collect::<Result<Vec<&dyn Stage>>>()?;
+
+        let mut metadata = Metadata::read(&ctx.output).await;
+        let mut data = Dataset::default();
+        let mut summary = Summary {
+            reports: Reports::read(&ctx.output).await,
+            ..Summary::default()
+        };
+
+        // Content hashes of finished data sets, stages that didn't produce anything usable are missing
+        let mut hashes = BTreeMap::new();
+        let mut finished = BTreeSet::new();
+
+        while !pending.is_empty() {
+            let (ready, waiting) = pending.into_iter()
+                .partition::<Vec<&dyn Stage>, _>(|s|s.dependencies().iter().all(|d|finished.contains(d)));
+
+            if ready.is_empty() {
+                return Err(WikibaseError::Config(format!(
+                    "Unable to resolve stage order for {}",
+                    waiting.iter().map(|s|s.kind().name()).collect::<Vec<&str>>().join(", ")
+                )))
+            }
+
+            pending = waiting;
+
+            // Stages which can't run as something they depend on is not there
+            let (ready, blocked) = ready.into_iter()
+                .partition::<Vec<&dyn Stage>, _>(|s|s.dependencies().iter().all(|d|
+                    hashes.contains_key(d) || !self.stage(*d).map(|d|d.required()).unwrap_or(true)
+                ));
+
+            for s in blocked {
+                let missing = s.dependencies().iter()
+                    .filter(|d|!hashes.contains_key(*d))
+                    .map(|d|d.name())
+                    .collect::<Vec<&str>>();
+
+                warn!("Skipping {} as {} failed", s.kind(), missing.join(", "));
+                finished.insert(s.kind());
+
+                summary.stages.push(StageSummary {
+                    kind: s.kind(),
+                    required: s.required(),
+                    status: Status::Skipped(format!("{} failed", missing.join(", "))),
+                    items: 0,
+                    skipped: 0,
+                    elapsed: Duration::ZERO,
+                });
+            }
+
+            let mut inputs = Vec::new();
+
+            for s in &ready {
+                inputs.push(fingerprint(s.kind(), s.dependencies(), &s.inputs(ctx).await, &hashes));
+            }
+
+            let results = join_all(ready.iter().zip(inputs).map(|(s, inputs)|
+                self.execute(*s, ctx, &data, &metadata, &summary.reports, inputs)
+            )).await;
+
+            for (s, (result, elapsed)) in ready.into_iter().zip(results) {
+                finished.insert(s.kind());
+
+                let Run { output, status, meta, report } = match result {
+                    Ok(r) => r,
+                    Err(e) => {
+                        error!("Stage {} failed: {}", s.kind(), e);
+
+                        summary.stages.push(StageSummary {
+                            kind: s.kind(),
+                            required: s.required(),
+                            status: Status::Failed(e.kind(), e.to_string()),
+                            items: 0,
+                            skipped: 0,
+                            elapsed,
+                        });
+
+                        continue;
+                    }
+                };
+
+                hashes.insert(s.kind(), meta.hash.clone());
+                metadata.datasets.insert(s.kind(), meta);
+
+                summary.stages.push(StageSummary {
+                    kind: s.kind(),
+                    required: s.required(),
+                    status,
+                    items: output.len(),
+                    skipped: report.len(),
+                    elapsed,
+                });
+
+                summary.reports.datasets.insert(s.kind(), report);
+
+                output.apply(&mut data);
+
+                if let Err(e) = s.finish(ctx, &data).await {
+                    error!("Stage {} failed to finish: {}", s.kind(), e);
+
+                    if let Some(l) = summary.stages.last_mut() {
+                        l.status = Status::Failed(e.kind(), e.to_string());
+                    }
+
+                    hashes.remove(&s.kind());
+                }
+            }
+
+            metadata.write(&ctx.output).await?;
+            summary.reports.write(&ctx.output).await?;
+        }
+
+        Ok((data, summary))
+    }
+    // Read previous results if they're still valid, otherwise run the stage and write its results
+    async fn execute(&self, stage: &dyn Stage, ctx: &Context, data: &Dataset, metadata: &Metadata, reports: &Reports, inputs: String)
+    -> (Result<Run>, Duration) {
+        let started = Instant::now();
+        let kind = stage.kind();
+        let path = ctx.output.join(kind.file_name());
+        let previous = metadata.get(kind);
+
+        // Results from before metadata was recorded are trusted as they are
+        let unchanged = previous.is_none_or(|m|m.inputs == inputs);
+        let stale = match ctx.max_age {
+            Some(a) => previous_age(previous, &path).await.is_none_or(|age|age > a),
+            None => false,
+        };
+
+        if !ctx.refetch(kind) && !stale && unchanged && path.exists() {
+            match read_output(kind, &path).await {
+                Ok(o) => {
+                    info!("Using previous {} data from {}", kind, path.to_string_lossy());
+                    let report = reports.get(kind).cloned().unwrap_or_default();
+                    return (cached(o, previous, report, &path, inputs).await, started.elapsed())
+                },
+                Err(e) => warn!("Building {} again: {}", kind, e),
+            }
+        }
+
+        if stale && !ctx.refetch(kind) {
+            info!("Fetching {} again as previous results are too old", kind);
+        }
+        else if !unchanged {
+            info!("Building {} again as data sets it depends on have changed", kind);
+        }
+        else if !ctx.selected.contains(&kind) {
+            info!("Building {} as other data sets depend on it", kind);
+        }
+
+        // Keep track of the documents this stage alone fetches
+        let ctx = Context {
+            fetcher: ctx.fetcher.tracked(),
+            ..ctx.clone()
+        };
+
+        let result = match stage.run(&ctx, data).await {
+            Ok((mut o, report)) => {
+                o.normalize();
+
+                // Data set as the previous build left it, for `diff` to compare with
+                let written = match snapshot(&ctx.output, kind).await {
+                    Ok(_) => write_output(&o, &path).await,
+                    Err(e) => Err(e),
+                };
+
+                match written {
+                    Ok(json) => Ok(Run {
+                        output: o,
+                        status: Status::Built,
+                        report,
+                        meta: DatasetMeta {
+                            fetched: SystemTime::now(),
+                            sources: ctx.fetcher.sources(),
+                            hash: hash(json.as_bytes()),
+                            inputs,
+                        },
+                    }),
+                    Err(e) => Err(e),
+                }
+            },
+            Err(e) => Err(e),
+        };
+
+        (result, started.elapsed())
+    }
+}
+
+// Previous results with their metadata and report, results without metadata are dated by the file modification time
+async fn cached(output: Output, previous: Option<&DatasetMeta>, report: Report, path: &Path, inputs: String) -> Result<Run> {
+    let meta = match previous {
+        Some(m) => DatasetMeta { inputs, ..m.clone() },
+        None => DatasetMeta {
+            fetched: modified(path).await.unwrap_or_else(SystemTime::now),
+            sources: Vec::new(),
+            hash: hash(output.to_json()?.as_bytes()),
+            inputs,
+        },
+    };
+
+    Ok(Run { output, status: Status::Cached, meta, report })
+}
+
+async fn previous_age(previous: Option<&DatasetMeta>, path: &Path) -> Option<Duration> {
+    match previous {
+        Some(m) => Some(m.age()),
+        None => modified(path).await.and_then(|t|SystemTime::now().duration_since(t).ok()),
+    }
+}
+
+async fn modified(path: &Path) -> Option<SystemTime> {
+    metadata(path).await.and_then(|m|m.modified()).ok()
+}
+
+async fn read_output(kind: DatasetKind, path: &Path) -> Result<Output> {
+    let json = read_to_string(path).await.map_err(|e|
+        WikibaseError::io(format!("Failed to read {} data from", kind), path, e)
+    )?;
+
+    Output::from_json(kind, &json)
+}
+
+async fn write_output(output: &Output, path: &Path) -> Result<String> {
+    let json = output.to_json()?;
+
+    // Nothing that breaks the contract of the data set ends up in the output
+    #[cfg(feature = "validate")]
+    validate(output.kind(), &json)?;
+
+    // Ending newline is left out of the hash, the data is the same either way
+    write(path, format!("{}\n", json)).await.map_err(|e|
+        WikibaseError::io(format!("Failed to write {} data to", output.kind()), path, e)
+    )?;
+
+    info!("Data set {} written to {}", output.kind(), path.to_string_lossy());
+    Ok(json)
+}
+
+fn sort_set(items: &mut Vec<Identifier>) {
+    items.sort();
+    items.dedup();
+}
+
+// Regions of a language by their identifier, the strongest known status is kept of a region listed twice
+fn sort_regions(regions: &mut Vec<LanguageRegion>) {
+    regions.sort_by(|a, b|(&a.region, a.status.is_none(), a.status).cmp(&(&b.region, b.status.is_none(), b.status)));
+    regions.dedup_by(|a, b|a.region == b.region);
+}
+
+// Endonyms are listed for each language they're in, the same one might be there twice
+fn dedup_endonyms(capital: &mut Capital) {
+    if let Some(e) = capital.endonyms.as_mut() {
+        let mut seen = BTreeSet::new();
+        e.retain(|n|seen.insert(n.clone()));
+    }
+}
+
+fn hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes).iter().map(|b|format!("{:02x}", b)).collect()
+}
+
+// Identifies the inputs of a stage by the content of the data sets it depends on and the other inputs it has
+fn fingerprint(kind: DatasetKind, dependencies: &[DatasetKind], other: &[(String, String)], hashes: &BTreeMap<DatasetKind, String>) -> String {
+    let mut inputs = kind.name().to_string();
+
+    for (name, value) in other {
+        inputs.push_str(&format!(";{}={}", name, value));
+    }
+
+    for d in dependencies {
+        inputs.push_str(&format!(";{}={}", d, hashes.get(d).map(|h|h.as_str()).unwrap_or("")));
+    }
+
+    hash(inputs.as_bytes())
+}
@@ -0,0 +1,312 @@
+use std::collections::BTreeMap;
+
+use async_trait::async_trait;
+use tokio::fs::read;
+use tracing::{debug, error, info, warn};
+
+use crate::error::Result;
+use crate::report::Report;
+use crate::types::{CallingCode, Capital, Country, Currency, Flag, Identifier, Language, Region, SovereignState, UNMember};
+use crate::urls::*;
+use crate::{Dataset, DatasetKind};
+
+use super::{hash, Context, Output, Stage};
+
+
+// Sovereign states recognized as UN members, other data sets are collected for these
+fn un_member_states(data: &Dataset) -> BTreeMap<Identifier, SovereignState> {
+    data.sovereign_states.iter()
+        .filter(|(_, s)|s.un_member)
+        .map(|(i, s)|(i.to_owned(), s.to_owned()))
+        .collect()
+}
+
+/// UN member states from un.org, country names are of interest
+pub struct UnNationsStage;
+
+#[async_trait(?Send)]
+impl Stage for UnNationsStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::UnNations
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[]
+    }
+    fn required(&self) -> bool {
+        // Sovereign states can be identified from the input countries alone
+        false
+    }
+    async fn run(&self, ctx: &Context, _data: &Dataset) -> Result<(Output, Report)> {
+        let n = UNMember::fetch_un_nations(&ctx.fetcher, UN_NATIONS, &ctx.countries).await?;
+        info!("Fetched {} UN member states from {}", n.len(), UN_NATIONS);
+
+        Ok((Output::UnNations(n), Report::default()))
+    }
+}
+
+/// Sovereign states from wikipedia, connected to ISO 3166 codes with UN members and input countries
+pub struct SovereignStatesStage;
+
+#[async_trait(?Send)]
+impl Stage for SovereignStatesStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::SovereignStates
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[DatasetKind::UnNations]
+    }
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let html = ctx.fetcher.get_html(SOVEREIGN_STATES).await?;
+        let (countries, report) = SovereignState::from_html(&html, &data.un_nations, &ctx.countries)?;
+
+        Ok((Output::SovereignStates(countries), report))
+    }
+    async fn finish(&self, _ctx: &Context, data: &Dataset) -> Result<()> {
+        // Check if we have 193? UN member states
+        let un_nation_count = match data.un_nations.is_empty() {
+            true => 193,
+            false => data.un_nations.len()
+        };
+
+        let un_member_states = un_member_states(data);
+
+        if un_member_states.len() != un_nation_count {
+            let nons = data.sovereign_states.values()
+                .filter(|s|!s.un_member)
+                .map(|s|s.to_string())
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            error!("Expected {} UN member states, got {}", un_nation_count, un_member_states.len());
+            warn!("Current non members are: {}", nons);
+        }
+
+        Ok(())
+    }
+}
+
+/// ISO 3166 codes from wikipedia, compared with our list of UN member states
+pub struct RegionsStage;
+
+#[async_trait(?Send)]
+impl Stage for RegionsStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::Regions
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[DatasetKind::SovereignStates]
+    }
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let un_member_states = un_member_states(data);
+        info!("Proceeding with {} UN member states", un_member_states.len());
+
+        let html = ctx.fetcher.get_html(ISO_3166).await?;
+        let (regions, report) = Region::from_html(&html, &un_member_states)?;
+
+        Ok((Output::Regions(regions), report))
+    }
+}
+
+/// Flags of UN member states, downloaded only if not present already
+pub struct FlagsStage;
+
+#[async_trait(?Send)]
+impl Stage for FlagsStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::Flags
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[DatasetKind::SovereignStates]
+    }
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let flags_dir = ctx.output.join("flags");
+        let mut flags = BTreeMap::new();
+        let mut flags_missing = BTreeMap::new();
+
+        for (id, country) in un_member_states(data) {
+            let path = flags_dir.join(id.as_str());
+
+            // Expected format if flag has been added already
+            if !ctx.refetch(self.kind()) && path.join("source.png").exists() {
+                debug!("Flag for {} found at {}", country.name_short, path.to_string_lossy());
+
+                flags.insert(id.to_owned(), Flag::new(id, path));
+                continue;
+            }
+
+            flags_missing.insert(id, country);
+        }
+
+        if !flags_missing.is_empty() {
+            info!("Found {} missing flags, trying to fetch...", flags_missing.len());
+
+            let html = ctx.fetcher.get_html(FLAGS).await?;
+            info!("Fetched flags data from {}", FLAGS);
+
+            let f = Flag::from_html(&ctx.fetcher, &html, &flags_missing, &flags_dir).await?;
+            info!("Downloaded {} flags", f.len());
+
+            for i in f {
+                flags.insert(i.sovereignity.to_owned(), i);
+            }
+        }
+
+        Ok((Output::Flags(flags), Report::default()))
+    }
+    #[cfg(feature = "flags")]
+    async fn finish(&self, ctx: &Context, data: &Dataset) -> Result<()> {
+        // Run transformations on the flags if not present
+        Flag::transform_flags(&data.flags, ctx.refetch(self.kind())).await
+    }
+}
+
+/// Flag emojis from wikipedia
+pub struct EmojisStage;
+
+#[async_trait(?Send)]
+impl Stage for EmojisStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::Emojis
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[DatasetKind::Regions]
+    }
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let html = ctx.fetcher.get_html(EMOJIS).await?;
+        let (emojis, report) = Flag::emojis_from_html(&html, &data.regions)?;
+
+        Ok((Output::Emojis(emojis), report))
+    }
+}
+
+/// Currencies from wikipedia, for regions in our list
+pub struct CurrenciesStage;
+
+#[async_trait(?Send)]
+impl Stage for CurrenciesStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::Currencies
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[DatasetKind::Regions]
+    }
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let html = ctx.fetcher.get_html(CURRENCIES).await?;
+        let (currencies, report) = Currency::from_html(&html, &data.regions, Some(&ctx.countries))?;
+
+        Ok((Output::Currencies(currencies), report))
+    }
+}
+
+/// Calling codes from wikipedia, for regions in our list
+pub struct CallingCodesStage;
+
+#[async_trait(?Send)]
+impl Stage for CallingCodesStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::CallingCodes
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[DatasetKind::Regions]
+    }
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let html = ctx.fetcher.get_html(CALLING_CODES).await?;
+        let (calling_codes, report) = CallingCode::from_html(&html, &data.regions, Some(&ctx.countries))?;
+
+        Ok((Output::CallingCodes(calling_codes), report))
+    }
+}
+
+/// ISO 639 language codes from wikipedia, matched with regions where the languages are spoken
+pub struct LanguagesStage;
+
+#[async_trait(?Send)]
+impl Stage for LanguagesStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::Languages
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[DatasetKind::Regions]
+    }
+    async fn inputs(&self, ctx: &Context) -> Vec<(String, String)> {
+        let Some(dir) = &ctx.iso_639_3 else {
+            return Vec::new()
+        };
+
+        let mut inputs = vec![("iso_639_3".to_string(), dir.to_string_lossy().to_string())];
+
+        // Edited code tables build the languages again, missing ones fail the build anyway
+        for name in Language::SIL_TABLES {
+            let content = read(dir.join(name)).await.map(|b|hash(&b)).unwrap_or_default();
+            inputs.push((name.to_string(), content));
+        }
+
+        inputs
+    }
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let html = ctx.fetcher.get_html(LANG_CODES_ISO_639).await?;
+        let (mut languages, mut report) = Language::from_html(&html)?;
+
+        match ctx.fetcher.get_html(LANG_ZONES).await {
+            Ok(d) => match Language::zones_from_html(&d, &ctx.countries, &data.regions, &mut languages) {
+                Ok(r) => report.extend(r),
+                Err(e) => error!("Failed to parse languages zones data: {}", e),
+            },
+            Err(e) => {
+                error!("Failed to fetch languages zones data: {}", e);
+            }
+        }
+
+        // Languages of the code tables are added after the zones, the thousands of names of their own would be matched in free text
+        if let Some(dir) = &ctx.iso_639_3 {
+            report.extend(Language::read_sil(dir, &mut languages).await?);
+            info!("{} languages with ISO 639-3 code tables of {}", languages.len(), dir.to_string_lossy());
+        }
+
+        Ok((Output::Languages(languages), report))
+    }
+}
+
+/// Capitals from wikipedia, for regions in our list
+pub struct CapitalsStage;
+
+#[async_trait(?Send)]
+impl Stage for CapitalsStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::Capitals
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[DatasetKind::Regions]
+    }
+    async fn run(&self, ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let html = ctx.fetcher.get_html(CAPITALS).await?;
+        let (capitals, report) = Capital::from_html(&html, &data.regions, Some(&ctx.countries))?;
+
+        Ok((Output::Capitals(capitals), report))
+    }
+}
+
+/// Every region joined with the other data sets, written as a whole and one file per country
+pub struct CountriesStage;
+
+#[async_trait(?Send)]
+impl Stage for CountriesStage {
+    fn kind(&self) -> DatasetKind {
+        DatasetKind::Countries
+    }
+    fn dependencies(&self) -> &'static [DatasetKind] {
+        &[
+            DatasetKind::SovereignStates, DatasetKind::Regions, DatasetKind::Flags, DatasetKind::Emojis,
+            DatasetKind::Currencies, DatasetKind::CallingCodes, DatasetKind::Languages, DatasetKind::Capitals
+        ]
+    }
+    async fn run(&self, _ctx: &Context, data: &Dataset) -> Result<(Output, Report)> {
+        let countries = Country::from_dataset(data);
+        info!("Joined {} countries from the other data sets", countries.len());
+
+        Ok((Output::Countries(countries), Report::default()))
+    }
+    async fn finish(&self, ctx: &Context, data: &Dataset) -> Result<()> {
+        Country::write_each(&data.countries, &ctx.output.join("countries")).await
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fmt::Result as Formatted;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs::{read_to_string, write};
+use tracing::{debug, warn};
+
+use crate::error::{Result, WikibaseError};
+use crate::map::Row;
+use crate::{DatasetKind, Identifier};
+
+/// Name of the file extraction reports are stored in, next to the data sets
+pub const REPORT_FILE: &str = "report.json";
+
+
+/// Table row that didn't make it to a data set
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Skipped {
+    /// Index of the table among the tables of the page
+    pub table: usize,
+    /// Index of the row among the data rows of the table
+    pub row: usize,
+    /// Text of the offending cell, or of the whole row if no single cell is to blame
+    pub text: String,
+    pub reason: String,
+}
+
+/// Phrase of a table row that was recognized as a known name
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Matched {
+    pub table: usize,
+    pub row: usize,
+    pub phrase: String,
+    /// Identifier the phrase names
+    pub id: Identifier,
+}
+
+/// Rows skipped while extracting a data set, and the names recognized in free text for auditing
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Report {
+    pub skipped: Vec<Skipped>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched: Vec<Matched>,
+}
+
+impl Report {
+    pub fn skip<T: Into<String>, R: Into<String>>(&mut self, table: usize, row: usize, text: T, reason: R) {
+        let text = text.into().split_whitespace().collect::<Vec<&str>>().join(" ");
+        let reason = reason.into();
+
+        debug!("Skipping row {} of table {} ('{}'): {}", row, table, text, reason);
+        self.skipped.push(Skipped { table, row, text, reason });
+    }
+    /// Skip a row collected from a table
+    pub(crate) fn skip_row<T: Into<String>, R: Into<String>>(&mut self, row: &Row, text: T, reason: R) {
+        self.skip(row.table, row.index, text, reason)
+    }
+    /// Record a phrase of a row collected from a table that named `id`
+    pub(crate) fn match_row<P: Into<String>>(&mut self, row: &Row, phrase: P, id: &Identifier) {
+        let phrase = phrase.into();
+
+        debug!("Matched '{}' of row {} of table {} to {}", phrase, row.index, row.table, id);
+        self.matched.push(Matched { table: row.table, row: row.index, phrase, id: id.clone() });
+    }
+    pub fn extend(&mut self, other: Report) {
+        self.skipped.extend(other.skipped);
+        self.matched.extend(other.matched);
+    }
+    pub fn len(&self) -> usize {
+        self.skipped.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.skipped.is_empty()
+    }
+    /// Number of skipped rows by reason
+    pub fn reasons(&self) -> BTreeMap<&str, usize> {
+        let mut reasons = BTreeMap::new();
+
+        for s in &self.skipped {
+            *reasons.entry(s.reason.as_str()).or_default() += 1;
+        }
+
+        reasons
+    }
+}
+
+/// Extraction reports of every data set in an output directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Reports {
+    pub datasets: BTreeMap<DatasetKind, Report>,
+}
+
+impl Reports {
+    /// Reads reports from given directory, missing or invalid reports are treated as empty
+    pub async fn read<P: AsRef<Path>>(dir: P) -> Self {
+        let path = dir.as_ref().join(REPORT_FILE);
+
+        if !path.exists() {
+            debug!("No previous report in {}", path.to_string_lossy());
+            return Self::default()
+        }
+
+        Self::from_file(&path).await.unwrap_or_else(|e| {
+            warn!("Ignoring previous report: {}", e);
+            Self::default()
+        })
+    }
+    pub async fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = read_to_string(path).await.map_err(|e|
+            WikibaseError::io("Failed to read report from", path, e)
+        )?;
+
+        serde_json::from_str(&json).map_err(|e|
+            WikibaseError::json(format!("report {}", path.to_string_lossy()), e)
+        )
+    }
+    pub async fn write<P: AsRef<Path>>(&self, dir: P) -> Result<()> {
+        let path = dir.as_ref().join(REPORT_FILE);
+        let json = serde_json::to_string_pretty(self).map_err(|e|WikibaseError::json("report", e))?;
+
+        write(&path, json).await.map_err(|e|
+            WikibaseError::io("Failed to write report to", &path, e)
+        )
+    }
+    pub fn get(&self, kind: DatasetKind) -> Option<&Report> {
+        self.datasets.get(&kind)
+    }
+}
+
+impl Display for Reports {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        writeln!(f, "{:<18} {:>7}  reason", "data set", "skipped")?;
+
+        for (kind, report) in self.datasets.iter().filter(|(_, r)|!r.is_empty()) {
+            for (reason, count) in report.reasons() {
+                writeln!(f, "{:<18} {:>7}  {}", kind.name(), count, reason)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use schemars::{schema_for, Schema};
+#[cfg(feature = "validate")]
+use serde_json::Value;
+use tokio::fs::write;
+use tracing::debug;
+
+use crate::error::{Result, WikibaseError};
+use crate::types::{CallingCode, Capital, Country, Currency, Flag, Identifier, Language, Region, SovereignState, UNMember};
+use crate::DatasetKind;
+
+// Validation errors listed in an error message, the rest are only counted
+#[cfg(feature = "validate")]
+const SHOWN_ERRORS: usize = 5;
+
+
+/// JSON Schema of a data set file, generated from the types it's serialized from
+pub fn schema(kind: DatasetKind) -> Schema {
+    let mut schema = match kind {
+        DatasetKind::UnNations => schema_for!(Vec<UNMember>),
+        DatasetKind::SovereignStates => schema_for!(BTreeMap<Identifier, SovereignState>),
+        DatasetKind::Regions => schema_for!(BTreeMap<Identifier, Region>),
+        DatasetKind::Flags => schema_for!(BTreeMap<Identifier, Flag>),
+        DatasetKind::Emojis => schema_for!(BTreeMap<Identifier, String>),
+        DatasetKind::Currencies => schema_for!(BTreeMap<Identifier, Currency>),
+        DatasetKind::CallingCodes => schema_for!(BTreeMap<Identifier, CallingCode>),
+        DatasetKind::Languages => schema_for!(BTreeMap<Identifier, Language>),
+        DatasetKind::Capitals => schema_for!(BTreeMap<Identifier, Capital>),
+        DatasetKind::Countries => schema_for!(BTreeMap<Identifier, Country>),
+    };
+
+    schema.insert("title".to_string(), kind.name().into());
+    schema
+}
+
+/// Checks data set JSON against the schema of the data set, every violation is listed in the error
+#[cfg(feature = "validate")]
+pub fn validate(kind: DatasetKind, json: &str) -> Result<()> {
+    let instance = serde_json::from_str::<Value>(json).map_err(|e|
+        WikibaseError::json(format!("{} data", kind), e)
+    )?;
+
+    let validator = jsonschema::validator_for(schema(kind).as_value()).map_err(|e|
+        WikibaseError::validation(kind, format!("Invalid schema: {}", e))
+    )?;
+
+    let errors = validator.iter_errors(&instance)
+        .map(|e|format!("'{}' {}", e.instance_path(), e))
+        .collect::<Vec<String>>();
+
+    if errors.is_empty() {
+        return Ok(())
+    }
+
+    let mut reason = format!("Data doesn't match its schema: {}", errors[..errors.len().min(SHOWN_ERRORS)].join(", "));
+
+    if errors.len() > SHOWN_ERRORS {
+        reason.push_str(&format!(" and {} more", errors.len() - SHOWN_ERRORS));
+    }
+
+    Err(WikibaseError::validation(kind, reason))
+}
+
+/// Writes the schema of each data set next to the data set file
+pub async fn write_schemas<P, I>(dir: P, kinds: I) -> Result<()>
+where P: AsRef<Path>, I: IntoIterator<Item = DatasetKind> {
+    for kind in kinds {
+        let path = dir.as_ref().join(kind.schema_file_name());
+        let json = serde_json::to_string_pretty(&schema(kind)).map_err(|e|
+            WikibaseError::json(format!("{} schema", kind), e)
+        )?;
+
+        write(&path, json).await.map_err(|e|
+            WikibaseError::io(format!("Failed to write {} schema to", kind), &path, e)
+        )?;
+
+        debug!("Schema of {} written to {}", kind, path.to_string_lossy());
+    }
+
+    Ok(())
+}
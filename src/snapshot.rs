@@ -0,0 +1,97 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use tokio::fs::{create_dir_all, read, write};
+
+use crate::error::{Result, WikibaseError};
+
+
+/// Directory of recorded http responses, one file per url
+#[derive(Debug, Clone)]
+pub struct Snapshots {
+    dir: PathBuf,
+}
+
+impl Snapshots {
+    pub fn new<P>(dir: P) -> Self where P: AsRef<Path> {
+        Self {
+            dir: dir.as_ref().to_path_buf()
+        }
+    }
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    /// File where the response of `url` is stored: `<dir>/<host>/<path>`, with a hash of the path and query
+    /// appended to the file name when either can't be stored as is
+    pub fn path(&self, url: &str) -> Result<PathBuf> {
+        let rest = url.split_once("://").map(|(_, r)|r).unwrap_or(url);
+        let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+
+        if host.is_empty() {
+            return Err(WikibaseError::Config(format!("Unable to resolve snapshot path for url without host: {}", url)))
+        }
+
+        let (path_only, query) = match path.split_once('?') {
+            Some((p, q)) => (p, Some(q)),
+            None => (path, None),
+        };
+
+        // Directories of the path are kept, characters that aren't safe on every file system are replaced
+        let mut segments = path_only.split('/')
+            .filter(|s|!s.is_empty())
+            .map(|s|match s {
+                "." | ".." => "_".to_string(),
+                s => s.chars().map(|c|match c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                    true => c,
+                    false => '_',
+                }).collect::<String>(),
+            })
+            .collect::<Vec<String>>();
+
+        // Replaced characters, queries and trailing slashes would make different urls share a file
+        let altered = query.is_some() || path_only.ends_with('/') || segments.join("/") != path_only.trim_start_matches('/');
+
+        let mut name = segments.pop().unwrap_or_else(||"index".to_string());
+
+        // Pages usually have no extension, mark them as html so snapshots can be opened in a browser
+        let extension = match name.rsplit_once('.') {
+            Some((n, e)) if !n.is_empty() && (1..=4).contains(&e.len()) && e.chars().all(|c|c.is_ascii_alphanumeric()) => {
+                let e = e.to_string();
+                name.truncate(n.len());
+                e
+            },
+            _ => "html".to_string(),
+        };
+
+        if altered {
+            let digest = Sha256::digest(path.as_bytes());
+            name.push('-');
+            name.extend(digest.iter().take(4).map(|b|format!("{:02x}", b)));
+        }
+
+        let mut file = self.dir.join(host);
+        file.extend(segments);
+
+        Ok(file.join(format!("{}.{}", name, extension)))
+    }
+    pub async fn read(&self, url: &str) -> Result<Vec<u8>> {
+        let path = self.path(url)?;
+
+        read(&path).await.map_err(|e|
+            WikibaseError::io(format!("No snapshot of {} at", url), &path, e)
+        )
+    }
+    pub async fn write(&self, url: &str, body: &[u8]) -> Result<()> {
+        let path = self.path(url)?;
+
+        if let Some(p) = path.parent() {
+            create_dir_all(p).await.map_err(|e|
+                WikibaseError::io("Failed to create snapshot directory", p, e)
+            )?;
+        }
+
+        write(&path, body).await.map_err(|e|
+            WikibaseError::io(format!("Failed to write snapshot of {} to", url), &path, e)
+        )
+    }
+}
@@ -1,86 +1,189 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fmt::Result as Formatted;
-use anyhow::{bail, Result};
+use std::str::FromStr;
 use scraper::Html;
-use serde::{Deserialize, Serialize};
-use tracing::{debug, warn};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize};
+use schemars::JsonSchema;
+use tracing::debug;
 
+use crate::error::{Result, WikibaseError};
 use crate::map::{Include, Found, map_from_table_data, Select};
+use crate::phone::{PrefixTrie, NANP};
+use crate::report::Report;
 use crate::types::region::region_by_opt;
 use crate::types::link_text_if;
+use crate::DatasetKind;
 
 use super::{Identifier, Region};
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
-pub struct CallingCode (pub String);
+const DATASET: DatasetKind = DatasetKind::CallingCodes;
+
+// Regions dialed with the bare country code of the North American Numbering Plan, the page doesn't always give them a row of their own
+const NANP_MEMBERS: [&str; 2] = ["ca", "us"];
+
+/// ITU country calling code, with the area codes that tell regions sharing the country code apart
+#[derive(Debug, Clone, Serialize, JsonSchema, Eq, PartialEq, Ord, PartialOrd)]
+pub struct CallingCode {
+    pub country_code: u16,
+    /// National prefixes following the country code, such as 268 of Antigua and Barbuda in +1 268
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub area_codes: Vec<String>,
+    /// World numbering zone, the first digit of the country code
+    pub zone: u8,
+}
+
+// Data sets written before calling codes were split to parts hold them as text, such as "1 (268)"
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Stored {
+    Text(String),
+    Parts {
+        country_code: u16,
+        #[serde(default)]
+        area_codes: Vec<String>,
+    },
+}
+
+impl<'de> Deserialize<'de> for CallingCode {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error> where D: Deserializer<'de> {
+        match Stored::deserialize(deserializer)? {
+            Stored::Text(t) => t.parse().map_err(D::Error::custom),
+            Stored::Parts { country_code, area_codes } => Ok(Self::new(country_code, area_codes)),
+        }
+    }
+}
 
 impl Display for CallingCode {
     fn fmt(&self, f: &mut Formatter) -> Formatted {
-        write!(f, "{}", self.0)
+        match self.area_codes.is_empty() {
+            true => write!(f, "{}", self.country_code),
+            false => write!(f, "{} ({})", self.country_code, self.area_codes.join(", ")),
+        }
+    }
+}
+
+impl FromStr for CallingCode {
+    type Err = String;
+
+    /// Reads a code the way Wikipedia lists them, `358` or `1 (809, 829, 849)`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = ||format!("Invalid calling code '{}'", s);
+        let (code, areas) = match s.trim().split_once('(') {
+            Some((c, a)) => (c, a.trim_end().strip_suffix(')').ok_or_else(invalid)?),
+            None => (s, ""),
+        };
+
+        let code = code.trim();
+        let areas = areas.split(',').map(|a|a.trim()).filter(|a|!a.is_empty()).map(|a|a.to_string()).collect::<Vec<String>>();
+
+        // Country codes are one to three digits, area codes digits of any length
+        if !(1..=3).contains(&code.len()) || areas.iter().any(|a|!a.chars().all(|c|c.is_ascii_digit())) {
+            return Err(invalid())
+        }
+
+        code.parse::<u16>().map(|c|Self::new(c, areas)).map_err(|_|invalid())
     }
 }
 
 impl CallingCode {
-    pub fn new(code: String) -> Self {
-        Self(code)
+    pub fn new(country_code: u16, area_codes: Vec<String>) -> Self {
+        let zone = country_code.to_string().bytes().next().map(|d|d - b'0').unwrap_or_default();
+
+        Self {
+            country_code,
+            area_codes,
+            zone,
+        }
+    }
+    /// Digits an E.164 number of the region starts with, one for each area code or the country code alone
+    pub fn prefixes(&self) -> Vec<String> {
+        match self.area_codes.is_empty() {
+            true => vec![self.country_code.to_string()],
+            false => self.area_codes.iter().map(|a|format!("{}{}", self.country_code, a)).collect(),
+        }
+    }
+    /// Regions an E.164 number or a prefix of one might belong to, the candidates `PrefixTrie::classify` finds.
+    /// Compiles the calling codes for one lookup, build a `PrefixTrie` once to look up many numbers.
+    pub fn regions_of(codes: &BTreeMap<Identifier, Self>, number: &str) -> Vec<Identifier> {
+        PrefixTrie::new(codes).classify(number).map(|n|n.candidates).unwrap_or_default()
     }
     pub fn from_html(html: &Html, iso_3166: &BTreeMap<Identifier, Region>, countries: Option<&BTreeMap<Identifier, Vec<String>>>)
-    -> Result<BTreeMap<Identifier, Self>> {
-        let mut cols = HashMap::new();
-        cols.insert(0, Some(Select::TdElement)); // country where used
-        cols.insert(1, Some(Select::Matching("a"))); // code
-        cols.insert(2, None);
-        cols.insert(3, None);
-
-        let collect = Include::Some { th_count: 5, td_map: cols };
+    -> Result<(BTreeMap<Identifier, Self>, Report)> {
+        let collect = Include::new()
+            .column_matching("country", "(?i)^country", Select::TdElement) // country where used
+            .column("Code", Select::Matching("a"));
         let mut items: BTreeMap<Identifier, CallingCode> = BTreeMap::new();
+        let mut report = Report::default();
     
-        for m in map_from_table_data(html, collect, None)? {
+        for m in map_from_table_data(html, DATASET, collect, None, &mut report)? {
             // Read the calling code first
-            let code = match m.get(&1) {
+            let code = match m.get("Code") {
                 Some(i) => match i {
-                    Found::Children(v) => match v.into_iter().find_map(|e|link_text_if("/wiki/", *e)) {
+                    Found::Children(v) => match v.iter().find_map(|e|link_text_if("/wiki/", *e)) {
                         Some(c) => c,
                         None => {
-                            debug!("Expected calling code link text (most likely another table with same column count)");
+                            report.skip_row(&m, m["Code"].text(), "No calling code link");
                             continue;
                         },
                     },
-                    _ => bail!("Expected link element in calling code column"),
+                    _ => return Err(WikibaseError::layout(DATASET, "Expected link element in calling code column")),
                 },
-                None => bail!("Expected calling code column"),
+                None => return Err(WikibaseError::layout(DATASET, "Expected calling code column")),
             };
 
             // Read in which country the code serves
-            let vals = match m.get(&0) {
+            let vals = match m.get("country") {
                 Some(i) => match i {
-                    Found::Parent(e) => e.text().into_iter().take(2).map(|s|s.trim().to_string()).collect::<Vec<String>>(),
-                    _ => bail!("Expected TD element children for country column"),
+                    Found::Parent(e) => e.text().map(|s|s.trim()).filter(|s|!s.is_empty()).take(2).map(|s|s.to_string()).collect::<Vec<String>>(),
+                    _ => return Err(WikibaseError::layout(DATASET, "Expected TD element children for country column")),
                 },
-                None => bail!("Expected country column"),
+                None => return Err(WikibaseError::layout(DATASET, "Expected country column")),
             };
 
-            debug!("Processing calling code {} of {:?} ({:?})", code, vals.get(0), vals.get(1));
+            if vals.is_empty() {
+                report.skip_row(&m, m["Code"].text(), "No country name");
+                continue;
+            }
+
+            debug!("Processing calling code {} of {:?} ({:?})", code, vals.first(), vals.get(1));
 
             // Find the iso 3166 identifier for the country
-            let iso_id = match region_by_opt(iso_3166, countries, vals.get(0), vals.get(1)) {
+            let iso_id = match region_by_opt(DATASET, m.index, iso_3166, countries, vals.first(), vals.get(1)) {
                 Ok(c) => c.0,
                 Err(e) => {
-                    warn!("Skipping calling code {}: {}", code, e);
+                    debug!("Skipping calling code {}: {}", code, e);
+                    report.skip_row(&m, m["country"].text(), "Country not found");
                     continue;
                 }
             };
 
             if items.contains_key(&iso_id) {
-                warn!("Skipping calling code {}: Duplicate entry", iso_id);
+                report.skip_row(&m, m["country"].text(), "Duplicate entry");
                 continue;
             }
 
-            items.insert(iso_id, CallingCode::new(code));
+            let code = match code.parse::<CallingCode>() {
+                Ok(c) => c,
+                Err(e) => {
+                    debug!("Skipping calling code of {}: {}", iso_id, e);
+                    report.skip_row(&m, m["Code"].text(), "Invalid calling code");
+                    continue;
+                }
+            };
+
+            items.insert(iso_id, code);
         }
-    
-        Ok(items)
+
+        // Numbering plan is on the page, its members without a row are added
+        if items.values().any(|c|c.country_code == NANP) {
+            for id in NANP_MEMBERS.map(Identifier::new).into_iter().filter(|i|iso_3166.contains_key(i)) {
+                items.entry(id).or_insert_with(||Self::new(NANP, Vec::new()));
+            }
+        }
+
+        Ok((items, report))
     }
 }
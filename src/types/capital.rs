@@ -1,19 +1,24 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fmt::Result as Formatted;
-use anyhow::{bail, Result};
 use scraper::Html;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, warn};
+use schemars::JsonSchema;
+use tracing::debug;
 
+use crate::error::{Result, WikibaseError};
 use crate::map::{Include, Found, map_from_table_data, Select};
+use crate::report::Report;
 use crate::types::region::region_by_opt;
 use crate::types::{link_text_if, link_title_and_text_opt_if};
+use crate::DatasetKind;
 
 use super::{Identifier, Region};
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+const DATASET: DatasetKind = DatasetKind::Capitals;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Capital {
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -37,34 +42,37 @@ impl Capital {
         }
     }
     pub fn from_html(html: &Html, regions: &BTreeMap<Identifier, Region>, countries: Option<&BTreeMap<Identifier, Vec<String>>>)
-    -> Result<BTreeMap<Identifier, Self>> {
-        let mut cols = HashMap::new();
-        cols.insert(0, Some(Select::Matching("a")));
-        cols.insert(1, Some(Select::Matching("a")));
-        cols.insert(2, None);
-        cols.insert(3, Some(Select::Matching("span[lang]")));
-        cols.insert(4, None); 
-
-        let collect = Include::Some { th_count: 5, td_map: cols };
+    -> Result<(BTreeMap<Identifier, Self>, Report)> {
+        let collect = Include::new()
+            .column("Country (exonym)", Select::Matching("a"))
+            .column("Capital (exonym)", Select::Matching("a"))
+            .column("Capital (endonym)", Select::Matching("span[lang]"));
         let mut items: BTreeMap<Identifier, Capital> = BTreeMap::new();
+        let mut report = Report::default();
     
-        for m in map_from_table_data(html, collect, None)? {
+        for m in map_from_table_data(html, DATASET, collect, None, &mut report)? {
             // Read country name
-            let (reg_title, reg_text) = match m.get(&0) {
+            let (reg_title, reg_text) = match m.get("Country (exonym)") {
                 Some(i) => match i {
                     Found::Children(v) => link_title_and_text_opt_if("/wiki/", v),
-                    _ => bail!("Expected TD element children for country column"),
+                    _ => return Err(WikibaseError::layout(DATASET, "Expected TD element children for country column")),
                 },
-                None => bail!("Expected country column"),
+                None => return Err(WikibaseError::layout(DATASET, "Expected country column")),
             };
 
             debug!("Processing capital of {:?} ({:?})", reg_title, reg_text);
 
+            if reg_title.is_none() && reg_text.is_none() {
+                report.skip_row(&m, m["Country (exonym)"].text(), "No country name");
+                continue;
+            }
+
             // Find the country in the map of regions
-            let (iso_id, region) = match region_by_opt(regions, countries, reg_title.as_ref(), reg_text.as_ref()) {
+            let (iso_id, region) = match region_by_opt(DATASET, m.index, regions, countries, reg_title.as_ref(), reg_text.as_ref()) {
                 Ok(c) => c,
                 Err(e) => {
-                    warn!("Skipping capital for {:?} / {:?}: {}", reg_text, reg_title, e);
+                    debug!("Skipping capital for {:?} / {:?}: {}", reg_text, reg_title, e);
+                    report.skip_row(&m, m["Country (exonym)"].text(), "Country not found");
                     continue;
                 }
             };
@@ -72,22 +80,22 @@ impl Capital {
             debug!("Found capital for {}", region);
 
             // Read capital name exonym
-            let name = match m.get(&1) {
+            let name = match m.get("Capital (exonym)") {
                 Some(i) => match i {
-                    Found::Children(v) => match v.into_iter().find_map(|i|link_text_if("/wiki/", *i)) {
+                    Found::Children(v) => match v.iter().find_map(|i|link_text_if("/wiki/", *i)) {
                         Some(s) => s,
                         None => {
-                            debug!("Expected link text for capital name of {}, failed to extract link from {} html elements", iso_id, v.len());
+                            report.skip_row(&m, m["Capital (exonym)"].text(), "No capital name link");
                             continue;
                         },
                     },
-                    _ => bail!("Expected TD element children for capital column"),
+                    _ => return Err(WikibaseError::layout(DATASET, "Expected TD element children for capital column")),
                 },
-                None => bail!("Expected capital column"),
+                None => return Err(WikibaseError::layout(DATASET, "Expected capital column")),
             };
 
             // Read capital name endonyms if any
-            let endonym = match m.get(&3) {
+            let endonym = match m.get("Capital (endonym)") {
                 Some(i) => match i {
                     Found::Children(v) => v.iter()
                         .filter_map(|s|
@@ -97,9 +105,9 @@ impl Capital {
                                 false => Some(s.trim().to_owned())
                             })
                         ).collect::<Vec<String>>(),
-                    _ => bail!("Expected inner text for {} capital endonym cell", iso_id),
+                    _ => return Err(WikibaseError::layout(DATASET, format!("Expected inner text for {} capital endonym cell", iso_id))),
                 },
-                None => bail!("Expected {} capital endonym column", iso_id),
+                None => return Err(WikibaseError::layout(DATASET, format!("Expected {} capital endonym column", iso_id))),
             };
 
             let endonyms = match endonym.len() {
@@ -111,6 +119,6 @@ impl Capital {
             items.insert(iso_id, Capital::new(name, endonyms));
         }
 
-        Ok(items)
+        Ok((items, report))
     }
 }
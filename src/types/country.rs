@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fmt::Result as Formatted;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use tokio::fs::{create_dir_all, read_dir, remove_file, write};
+use tracing::info;
+
+use crate::error::{Result, WikibaseError};
+use crate::{Dataset, DatasetKind};
+
+use super::{CallingCode, Capital, Currency, Fraction, Identifier, Iso639, Language, LanguageStatus, Region, SovereignState};
+
+
+const DATASET: DatasetKind = DatasetKind::Countries;
+
+/// Region joined with everything the other data sets know of it
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Country {
+    #[serde(flatten)]
+    pub region: Region,
+    /// Sovereign state the region belongs to, the region itself if it's a sovereign state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sovereign_state: Option<SovereignState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capital: Option<Capital>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calling_code: Option<CallingCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<String>,
+    /// Source image and transformations of the flag
+    #[serde(default)]
+    pub flag: Vec<PathBuf>,
+    /// Currencies in use by their ISO 4217 code
+    #[serde(default)]
+    pub currencies: BTreeMap<Identifier, CountryCurrency>,
+    /// Languages spoken by their ISO 639-3 code
+    #[serde(default)]
+    pub languages: BTreeMap<Identifier, CountryLanguage>,
+}
+
+/// Currency as used in one country, the regions of the currency are left to the currencies data set
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Eq, PartialEq)]
+pub struct CountryCurrency {
+    pub name: String,
+    pub symbol: String,
+    pub fraction: Fraction,
+}
+
+impl Display for CountryCurrency {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        write!(f, "{} ({})", self.name, self.symbol)
+    }
+}
+
+impl From<&Currency> for CountryCurrency {
+    fn from(currency: &Currency) -> Self {
+        Self {
+            name: currency.name.to_owned(),
+            symbol: currency.symbol.to_owned(),
+            fraction: currency.fraction.to_owned(),
+        }
+    }
+}
+
+/// Language as spoken in one country, the regions of the language are left to the languages data set
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct CountryLanguage {
+    /// Short name of the language
+    // Countries written before the view embedded the whole language under its own field names
+    #[serde(alias = "name_short")]
+    pub name: String,
+    pub iso639: Iso639,
+    /// Status of the language in this country, unknown for languages read from data sets written before statuses were recorded
+    #[serde(default)]
+    pub status: Option<LanguageStatus>,
+}
+
+impl Display for CountryLanguage {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        write!(f, "{}", self.name)
+    }
+}
+
+impl CountryLanguage {
+    /// View of the language in the region, `None` if it isn't spoken there
+    pub fn of(language: &Language, region: &Identifier) -> Option<Self> {
+        let link = language.region(region)?;
+
+        Some(Self {
+            name: language.name_short.to_owned(),
+            iso639: language.iso639.to_owned(),
+            status: link.status,
+        })
+    }
+    /// Official in the country, languages of unknown status count as they were read from the official and regional columns alike
+    pub fn is_official(&self) -> bool {
+        self.status.is_none_or(|s|s.is_official())
+    }
+}
+
+impl Display for Country {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        match &self.emoji {
+            Some(e) => write!(f, "{} {}", e, self.region),
+            None => write!(f, "{}", self.region),
+        }
+    }
+}
+
+impl Country {
+    /// Every region of the data set with the data other data sets have of it
+    pub fn from_dataset(data: &Dataset) -> BTreeMap<Identifier, Self> {
+        data.regions.iter()
+            .map(|(id, region)|{
+                let currencies = data.currencies.iter()
+                    .filter(|(_, c)|c.regions.contains(id))
+                    .map(|(i, c)|(i.to_owned(), CountryCurrency::from(c)))
+                    .collect();
+
+                let languages = data.languages.iter()
+                    .filter_map(|(i, l)|CountryLanguage::of(l, id).map(|v|(i.to_owned(), v)))
+                    .collect();
+
+                let country = Self {
+                    region: region.to_owned(),
+                    sovereign_state: data.sovereign_states.get(&region.sovereignity).cloned(),
+                    capital: data.capitals.get(id).cloned(),
+                    calling_code: data.calling_codes.get(id).cloned(),
+                    emoji: data.emojis.get(id).cloned(),
+                    flag: data.flags.get(id).map(|f|f.paths()).unwrap_or_default(),
+                    currencies,
+                    languages,
+                };
+
+                (id.to_owned(), country)
+            })
+            .collect()
+    }
+    /// Languages official in the country, or all of them for data sets written before statuses were recorded
+    pub fn official_languages(&self) -> impl Iterator<Item = (&Identifier, &CountryLanguage)> {
+        self.languages.iter().filter(|(_, l)|l.is_official())
+    }
+    /// Writes each country to its own file named by its ISO 3166-1 alpha-2 code, files of countries no longer there are removed
+    pub async fn write_each(countries: &BTreeMap<Identifier, Self>, dir: &Path) -> Result<()> {
+        create_dir_all(dir).await.map_err(|e|
+            WikibaseError::io("Failed to create countries directory", dir, e)
+        )?;
+
+        let names = countries.values()
+            .map(|c|format!("{}.json", c.region.iso_3166_1.a2.to_lowercase()))
+            .collect::<Vec<String>>();
+
+        let mut entries = read_dir(dir).await.map_err(|e|WikibaseError::io("Failed to read countries directory", dir, e))?;
+
+        while let Some(entry) = entries.next_entry().await.map_err(|e|WikibaseError::io("Failed to read countries directory", dir, e))? {
+            let path = entry.path();
+            let stale = path.extension().is_some_and(|e|e == "json") && !names.iter().any(|n|path.ends_with(n));
+
+            if stale {
+                remove_file(&path).await.map_err(|e|WikibaseError::io("Failed to remove country", &path, e))?;
+            }
+        }
+
+        for (country, name) in countries.values().zip(&names) {
+            let path = dir.join(name);
+            let json = serde_json::to_string_pretty(country).map_err(|e|
+                WikibaseError::json(format!("country {}", country.region), e)
+            )?;
+
+            // Same ending newline as the data set files
+            write(&path, format!("{}\n", json)).await.map_err(|e|
+                WikibaseError::io(format!("Failed to write {} data to", DATASET), &path, e)
+            )?;
+        }
+
+        info!("Wrote {} countries to {}", countries.len(), dir.to_string_lossy());
+        Ok(())
+    }
+}
@@ -1,19 +1,24 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fmt::Result as Formatted;
-use anyhow::{bail, Result};
 use scraper::Html;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info, warn};
+use schemars::JsonSchema;
+use tracing::{debug, info};
 
+use crate::error::{Result, WikibaseError};
 use crate::map::{Include, Found, map_from_table_data, Select};
+use crate::report::Report;
 use crate::types::region::region_by_opt;
 use crate::types::{link_text_if, link_title_and_text_opt_if};
+use crate::DatasetKind;
 
 use super::{inner_text_first_if, Identifier, Region};
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+const DATASET: DatasetKind = DatasetKind::Currencies;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Fraction {
     pub name: String,
     pub basic: u16,
@@ -34,7 +39,7 @@ impl Fraction {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Currency {
     pub name: String,
     pub symbol: String,
@@ -61,53 +66,58 @@ impl Currency {
         }
     }
     pub fn from_html(html: &Html, regions: &BTreeMap<Identifier, Region>, countries: Option<&BTreeMap<Identifier, Vec<String>>>)
-    -> Result<BTreeMap<Identifier, Self>> {
-        let mut cols = HashMap::new();
-        cols.insert(0, Select::Matching("a")); // country where used
-        cols.insert(1, Select::Matching("a")); // name
-        cols.insert(2, Select::InnerAsText); // symbol
-        cols.insert(3, Select::InnerAsText); // iso code <- IMPORTANT
-        cols.insert(4, Select::Matching("a")); // fraction
-        cols.insert(5, Select::InnerAsText); // fraction digits to form one complete
-
-        let collect = Include::All { th_count: 6, td_map: cols };
+    -> Result<(BTreeMap<Identifier, Self>, Report)> {
+        let collect = Include::new()
+            .column("State or territory", Select::Matching("a")) // country where used
+            .column("Currency", Select::Matching("a"))
+            .column("Symbol", Select::InnerAsText)
+            .column("ISO code", Select::InnerAsText) // <- IMPORTANT
+            .column("Fractional unit", Select::Matching("a"))
+            .column("Number to basic", Select::InnerAsText); // fraction digits to form one complete
         let mut items: BTreeMap<Identifier, Currency> = BTreeMap::new();
+        let mut report = Report::default();
     
-        for m in map_from_table_data(html, collect, None)? {
+        for m in map_from_table_data(html, DATASET, collect, None, &mut report)? {
             // We collect each currency only once. Compare currency iso codes
-            let iso = match m.get(&3) {
+            let iso = match m.get("ISO code") {
                 Some(i) => match i {
-                    Found::InnerText(v) => match v.into_iter().find(|s|s.trim().len() == 3) {
+                    Found::InnerText(v) => match v.iter().find(|s|s.trim().len() == 3) {
                         Some(s) => Identifier::new(s),
                         None => {
-                            warn!("Skipping currency [{:?}] with invalid ISO code", v);
+                            report.skip_row(&m, m["ISO code"].text(), "Invalid ISO code");
                             continue;
                         }
                     },
-                    _ => bail!("Expected inner text for currency ISO code"),
+                    _ => return Err(WikibaseError::layout(DATASET, "Expected inner text for currency ISO code")),
                 },
                 None => {
-                    warn!("Skipping currency without ISO code");
+                    report.skip_row(&m, "", "No ISO code");
                     continue;
                 }
             };
 
             // Read in which country the currency is used
-            let (reg_title, reg_text) = match m.get(&0) {
+            let (reg_title, reg_text) = match m.get("State or territory") {
                 Some(i) => match i {
                     Found::Children(v) => link_title_and_text_opt_if("/wiki/", v),
-                    _ => bail!("Expected TD element children for country column"),
+                    _ => return Err(WikibaseError::layout(DATASET, "Expected TD element children for country column")),
                 },
-                None => bail!("Expected country column"),
+                None => return Err(WikibaseError::layout(DATASET, "Expected country column")),
             };
 
             debug!("Processing currency of {:?} ({:?})", reg_title, reg_text);
 
+            if reg_title.is_none() && reg_text.is_none() {
+                report.skip_row(&m, m["State or territory"].text(), "No country name");
+                continue;
+            }
+
             // Find the country in the map of regions
-            let (iso_id, region) = match region_by_opt(regions, countries, reg_title.as_ref(), reg_text.as_ref()) {
+            let (iso_id, region) = match region_by_opt(DATASET, m.index, regions, countries, reg_title.as_ref(), reg_text.as_ref()) {
                 Ok(c) => c,
                 Err(e) => {
-                    warn!("Skipping currency {}: {}", iso, e);
+                    debug!("Skipping currency {}: {}", iso, e);
+                    report.skip_row(&m, m["State or territory"].text(), "Country not found");
                     continue;
                 }
             };
@@ -121,25 +131,25 @@ impl Currency {
             }
 
             // Read in the currency name
-            let name = match m.get(&1) {
+            let name = match m.get("Currency") {
                 Some(i) => match i {
-                    Found::Children(v) => match v.into_iter().find_map(|e|link_text_if("/wiki/", *e)){
+                    Found::Children(v) => match v.iter().find_map(|e|link_text_if("/wiki/", *e)){
                         Some(s) => s,
                         None => {
-                            warn!("Skipping currency of {} with invalid name", region.name);
+                            report.skip_row(&m, m["Currency"].text(), "Invalid currency name");
                             continue;
                         },
                     },
-                    _ => bail!("Expected link element for currency of {}", region.name),
+                    _ => return Err(WikibaseError::layout(DATASET, format!("Expected link element for currency of {}", region.name))),
                 },
                 None => {
-                    warn!("Skipping currency of {} as it doesn't have a name", region.name);
+                    report.skip_row(&m, "", "No currency name");
                     continue;
                 }
             };
 
             // Read in the currency symbol
-            let symbol = match m.get(&2) {
+            let symbol = match m.get("Symbol") {
                 Some(i) => match i {
                     Found::InnerText(v) => match inner_text_first_if(1, None, v) {
                         Some(s) => match s.split(" ").next() {
@@ -147,50 +157,50 @@ impl Currency {
                             None => s,
                         },
                         None => {
-                            warn!("Skipping currency '{}' of {} with invalid symbol", name, region.name);
+                            report.skip_row(&m, m["Symbol"].text(), "Invalid symbol");
                             continue;
                         },
                     },
-                    _ => bail!("Expected inner text for symbol of currency '{}' of {}", name, region.name),
+                    _ => return Err(WikibaseError::layout(DATASET, format!("Expected inner text for symbol of currency '{}' of {}", name, region.name))),
                 },
                 None => {
-                    warn!("Skipping currency '{}' of {} as it doesn't have a symbol",name, region.name);
+                    report.skip_row(&m, "", "No symbol");
                     continue;
                 }
             };
 
             // Read in the fraction name
-            let fraction_name = match m.get(&4) {
+            let fraction_name = match m.get("Fractional unit") {
                 Some(i) => match i {
-                    Found::Children(v) => match v.into_iter().find_map(|e|link_text_if("/wiki/", *e)) {
+                    Found::Children(v) => match v.iter().find_map(|e|link_text_if("/wiki/", *e)) {
                         Some(s) => s,
                         None => {
-                            warn!("Skipping currency '{}' of {} with invalid fraction name", name, region.name);
+                            report.skip_row(&m, m["Fractional unit"].text(), "Invalid fraction name");
                             continue;
                         },
                     },
-                    _ => bail!("Expected link element for currency '{}' fraction of {}", name, region.name),
+                    _ => return Err(WikibaseError::layout(DATASET, format!("Expected link element for currency '{}' fraction of {}", name, region.name))),
                 },
                 None => {
-                    warn!("Skipping currency '{}' of {} as it doesn't have a fraction name", name, region.name);
+                    report.skip_row(&m, "", "No fraction name");
                     continue;
                 }
             };   
 
             // Read in the fraction basic
-            let fraction_basic = match m.get(&5) {
+            let fraction_basic = match m.get("Number to basic") {
                 Some(i) => match i {
-                    Found::InnerText(v) => match v.into_iter().find_map(|s|s.trim().parse::<u16>().ok()) {
+                    Found::InnerText(v) => match v.iter().find_map(|s|s.trim().parse::<u16>().ok()) {
                         Some(u) => u,
                         None => {
-                            warn!("Skipping currency '{}' of {} with invalid fraction basic", name, region.name);
+                            report.skip_row(&m, m["Number to basic"].text(), "Invalid fraction basic");
                             continue;
                         },
                     },
-                    _ => bail!("Expected inner text for fraction units to basic for currency '{}' of {}", name, region.name),
+                    _ => return Err(WikibaseError::layout(DATASET, format!("Expected inner text for fraction units to basic for currency '{}' of {}", name, region.name))),
                 },
                 None => {
-                    warn!("Skipping currency '{}' of {} as it doesn't have a fraction basic", name, region.name);
+                    report.skip_row(&m, "", "No fraction basic");
                     continue;
                 }
             };
@@ -199,6 +209,6 @@ impl Currency {
             items.insert(iso.clone(), Currency::new(name, symbol, Fraction::new(fraction_name, fraction_basic), Some(iso_id)));
         }
     
-        Ok(items)
+        Ok((items, report))
     }
 }
@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+
+use image::codecs::png::PngEncoder;
+use image::{ExtendedColorType, ImageEncoder};
+use image::{ImageBuffer, Rgba, RgbaImage};
+use image::{io::Reader as ImageReader, DynamicImage};
+use imageproc::drawing::draw_filled_circle_mut;
+use tokio::fs::write;
+use tracing::{debug, info};
+
+use crate::error::{Result, WikibaseError};
+use crate::DatasetKind;
+use super::{Flag, Identifier, TRANSFORMATIONS};
+
+
+const TRANSPARENT: Rgba<u8> = image::Rgba::<u8>([0, 0, 0, 0]);
+const WHITE: Rgba<u8> = image::Rgba::<u8>([255, 255, 255, 255]);
+const BLACK: Rgba<u8> = image::Rgba::<u8>([0, 0, 0, 255]);
+const BLUE: Rgba<u8> = image::Rgba::<u8>([0, 0, 255, 255]);
+const GREEN: Rgba<u8> = image::Rgba::<u8>([0, 255, 0, 255]);
+const RED: Rgba<u8> = image::Rgba::<u8>([255, 0, 0, 255]);
+const YELLOW: Rgba<u8> = image::Rgba::<u8>([255, 255, 0, 255]);
+
+
+impl Flag {
+    pub async fn transform_flags(flags: &BTreeMap<Identifier, Self>, overwrite: bool) -> Result<()> {
+        for (i, f) in flags {
+            let mut path = f.dir.clone();
+            path.push("source.png");
+
+            if !path.is_file() {
+                return Err(WikibaseError::io(
+                    format!("Flag source image for {} not found from", i),
+                    &path,
+                    io::Error::from(io::ErrorKind::NotFound)
+                ))
+            }
+
+            let missing = TRANSFORMATIONS.iter()
+                .filter_map(|t|{
+                    let p = f.dir.join(t);
+                    
+                    match !overwrite && p.is_file() {
+                        true => None,
+                        false => Some((*t, p))
+                    }
+                })
+                .collect::<Vec<(&str, PathBuf)>>();
+
+            if missing.is_empty() {
+                debug!("All transformations for {} already exist", i);
+                continue;
+            }
+
+            let mut img = image_reader(&path)?;
+
+            // Crop image into a max sized square for transformations that expect a square image
+            let (mut side, x, y) = match img.width() >= img.height() {
+                true => (
+                    img.height(),
+                    (img.width() - img.height()) / 2,
+                    0
+                ),
+                false => (
+                    img.width(),
+                    0,
+                    (img.height() - img.width()) / 2,
+                )
+            };
+
+            // Make sure our square sides are odd numbers
+            if side % 2 == 0 {
+                side -= 1;
+            }
+
+            // Crop image into a square
+            img = img.crop_imm(x, y, side, side);
+            let side = side as i32;
+
+            info!("Transforming flag {} into {} variations", i, missing.len());
+
+            for (t, p) in missing {
+                match t {
+                    "round.png" => {
+                        let round = round_from_rect(&img.to_rgba8(), side);
+                        png_writer(&round, &p).await?;
+                    },
+                    "round_bl.png" => {
+                        let round = framed_round_from_rect(&img.to_rgba8(), side, WHITE, BLACK);
+                        png_writer(&round, &p).await?;
+                    },
+                    "round_wh.png" => {
+                        let round = framed_round_from_rect(&img.to_rgba8(), side, BLACK, WHITE);
+                        png_writer(&round, &p).await?;
+                    },
+                    "round_b.png" => {
+                        let round = framed_round_from_rect(&img.to_rgba8(), side, WHITE, BLUE);
+                        png_writer(&round, &p).await?;
+                    },
+                    "round_r.png" => {
+                        let round = framed_round_from_rect(&img.to_rgba8(), side, WHITE, RED);
+                        png_writer(&round, &p).await?;
+                    },
+                    "round_g.png" => {
+                        let round = framed_round_from_rect(&img.to_rgba8(), side, WHITE, GREEN);
+                        png_writer(&round, &p).await?;
+                    },
+                    "round_y.png" => {
+                        let round = framed_round_from_rect(&img.to_rgba8(), side, WHITE, YELLOW);
+                        png_writer(&round, &p).await?;
+                    },
+                    _ => unreachable!()
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) fn image_reader(path: &PathBuf) -> Result<DynamicImage> {
+    // Read source image from file
+    let reader = ImageReader::open(path)
+        .and_then(|r|r.with_guessed_format())
+        .map_err(|e|WikibaseError::io("Failed to read flag image", path, e))?;
+
+    match reader.format() {
+        Some(f) => {
+            if f != image::ImageFormat::Png {
+                return Err(WikibaseError::validation(DatasetKind::Flags, "Expected flag image to be in PNG format"))
+            }
+        },
+        None => return Err(WikibaseError::validation(DatasetKind::Flags, "Unable to detect image format from flag file.")),
+    }
+
+    // Image is valid PNG image
+    reader.decode().map_err(|e|WikibaseError::io("Failed to decode flag image", path, io::Error::other(e)))
+}
+
+fn round_from_rect(buf: &ImageBuffer<Rgba<u8>, Vec<u8>>, size: i32) -> DynamicImage {
+    // Draw a white circle on a transparent background that is the same size as the cropped image
+    let half = size / 2;
+    let mut img = RgbaImage::from_pixel(size as u32, size as u32, TRANSPARENT);
+    
+    draw_filled_circle_mut(
+        &mut img,
+        (half, half),
+        half,
+        WHITE
+    );
+
+    substitute_color_px(&mut img, buf, WHITE);
+    
+    DynamicImage::ImageRgba8(img)
+}
+
+fn framed_round_from_rect(buf: &ImageBuffer<Rgba<u8>, Vec<u8>>, size: i32, substitute: Rgba<u8>, frame: Rgba<u8>) -> DynamicImage {
+    // Draw a white circle on a transparent background that is the same size as the cropped image
+    let half = size / 2;
+    let mut img = RgbaImage::from_pixel(size as u32, size as u32, TRANSPARENT);
+
+    // Draw a frame around the circle
+    draw_filled_circle_mut(
+        &mut img,
+        (half, half),
+        half - 2,
+        frame
+    );
+    
+    // Draw a (placeholder color) circle inside the frame
+    draw_filled_circle_mut(
+        &mut img,
+        (half, half),
+        half - 4,
+        substitute
+    );
+
+    // Substitute the placeholder color with the flag image
+    substitute_color_px(&mut img, buf, substitute);
+    
+    // Blur the image to make the frame look better
+    let mut img = DynamicImage::ImageRgba8(img)
+        .blur(0.6)
+        .into_rgba8();
+
+    // Draw new smaller (placeholder color) circle inside blurred image
+    draw_filled_circle_mut(
+        &mut img,
+        (half, half),
+        half - 6,
+        substitute
+    );
+
+    // Substitute the placeholder color with the flag image
+    substitute_color_px(&mut img, buf, substitute);
+
+    DynamicImage::ImageRgba8(img)
+}
+
+fn substitute_color_px(target: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, source: &ImageBuffer<Rgba<u8>, Vec<u8>>, color: Rgba<u8>) {
+    for (x, y, p) in target.enumerate_pixels_mut() {
+        if color.eq(p) {
+            p.0 = source.get_pixel(x, y).0;
+        }
+    }
+}
+
+async fn png_writer(image: &DynamicImage, path: &PathBuf) -> Result<()> {
+    let mut buf = vec![];
+
+    PngEncoder::new(&mut buf).write_image(
+        image.as_bytes(),
+        image.width(),
+        image.height(),
+        ExtendedColorType::Rgba8,
+    ).map_err(|e|WikibaseError::io("Failed to create PNG image", path, io::Error::other(e)))?;
+
+    write(path, buf).await.map_err(|e|
+        WikibaseError::io("Failed to write transformed flag as PNG to", path, e)
+    )
+}
@@ -1,47 +1,285 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fmt::Result as Formatted;
+use std::path::Path;
+use std::str::FromStr;
 
-use anyhow::{anyhow, bail, Result};
 use scraper::{ElementRef, Html};
-use serde::{Serialize, Deserialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use schemars::JsonSchema;
 use scraper::Selector;
-use tracing::{debug, info, warn};
+use tokio::fs::read_to_string;
+use tracing::{debug, info};
 
+use crate::error::{Result, WikibaseError};
 use crate::map::{Include, Found, map_from_table_data, Select};
+use crate::matcher::{NameMatch, NameMatcher};
+use crate::report::Report;
 use crate::types::link_title_and_text_opt_if;
 use crate::types::region::region_by_opt;
+use crate::DatasetKind;
 
 use super::{link_text_if, link_title_if, Identifier, Region};
 
-const EXCLUDE: [&str; 25] = [
-    "has", "of", "de", "are", "in", "their", "they", "none", "and", "all", "have",
-    "languages", "ethnic", "groups", "official", "territories", "facto",
-    "status", "spoken", "another", "native", "wherever", "predominate", "autonomous",
-    "republic"
+// ISO 639-3 code table of SIL and the individual languages of each macrolanguage, in the directory the code tables are extracted to
+const CODE_TABLE: &str = "iso-639-3.tab";
+const MACROLANGUAGES: &str = "iso-639-3-macrolanguages.tab";
+
+const DATASET: DatasetKind = DatasetKind::Languages;
+
+// Columns of the SIL code tables, in order
+const CODE_TABLE_COLUMNS: [&str; 8] = ["Id", "Part2b", "Part2t", "Part1", "Scope", "Language_Type", "Ref_Name", "Comment"];
+const MACROLANGUAGE_COLUMNS: [&str; 3] = ["M_Id", "I_Id", "I_Status"];
+
+// Language columns of the language zones table and the status of the languages in them
+const COLUMNS: [(&str, LanguageStatus); 4] = [
+    ("Official language", LanguageStatus::Official),
+    ("Regional language", LanguageStatus::Regional),
+    ("Minority language", LanguageStatus::Minority),
+    ("National language", LanguageStatus::National),
+];
+
+// Names Wikipedia uses for languages besides the ones in the ISO 639 table, by ISO 639-3 code,
+// names of language groups such as Sami are left out as they would match a single member
+const ALIASES: [(&str, &str); 15] = [
+    ("Mandarin", "zho"),
+    ("Standard Chinese", "zho"),
+    ("Farsi", "fas"),
+    ("Castilian", "spa"),
+    ("Flemish", "nld"),
+    ("Moldovan", "ron"),
+    ("Sesotho", "sot"),
+    ("Setswana", "tsn"),
+    ("Kiswahili", "swa"),
+    ("Haitian Creole", "hat"),
+    ("Maldivian", "div"),
+    ("Greenlandic", "kal"),
+    ("Northern Sami", "sme"),
+    ("Bokmål", "nob"),
+    ("Nynorsk", "nno"),
 ];
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Codes of a language, only ISO 639-3 has a code for every language
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Iso639 {
+    #[schemars(regex(pattern = r"^([a-z]{2})?$"))]
     set1: String,
+    #[schemars(regex(pattern = r"^([a-z]{3})?$"))]
     set2_t: String,
+    #[schemars(regex(pattern = r"^([a-z]{3})?$"))]
     set2_b: String,
+    #[schemars(regex(pattern = r"^[a-z]{3}$"))]
     set3: String,
 }
 
 impl Display for Iso639 {
     fn fmt(&self, f: &mut Formatter) -> Formatted {
-        write!(f, "{}", self.set1)
+        match self.set1.is_empty() {
+            true => write!(f, "{}", self.set3),
+            false => write!(f, "{}", self.set1),
+        }
+    }
+}
+
+impl Iso639 {
+    /// Two letter ISO 639-1 code, empty for languages without one
+    pub fn set1(&self) -> &str {
+        &self.set1
+    }
+    /// Three letter ISO 639-2 terminology code, empty for languages without one
+    pub fn set2_t(&self) -> &str {
+        &self.set2_t
+    }
+    /// Three letter ISO 639-2 bibliographic code, empty for languages without one
+    pub fn set2_b(&self) -> &str {
+        &self.set2_b
+    }
+    /// Three letter ISO 639-3 code
+    pub fn set3(&self) -> &str {
+        &self.set3
+    }
+}
+
+/// What an ISO 639-3 code stands for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageScope {
+    /// Language of its own
+    Individual,
+    /// Several closely related languages known by one name, such as Chinese
+    Macrolanguage,
+    /// Code for something other than a language, such as `und` for undetermined
+    Special,
+}
+
+impl Display for LanguageScope {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for LanguageScope {
+    type Err = String;
+
+    /// Reads a scope the way SIL code tables list them, `I`, `M` or `S`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "I" => Ok(Self::Individual),
+            "M" => Ok(Self::Macrolanguage),
+            "S" => Ok(Self::Special),
+            _ => Err(format!("Unknown language scope '{}'", s)),
+        }
+    }
+}
+
+impl LanguageScope {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Individual => "individual",
+            Self::Macrolanguage => "macrolanguage",
+            Self::Special => "special",
+        }
+    }
+}
+
+/// Whether a language is spoken today, was once or was made up
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageType {
+    /// Spoken today
+    Living,
+    /// Died out in the last few centuries
+    Extinct,
+    /// Died out in ancient times
+    Ancient,
+    /// Distinct stage of a language that is still spoken, such as Old English
+    Historical,
+    /// Made up, such as Esperanto
+    Constructed,
+    /// Not a language but a special code
+    Special,
+}
+
+impl Display for LanguageType {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        write!(f, "{}", self.name())
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl FromStr for LanguageType {
+    type Err = String;
+
+    /// Reads a type the way SIL code tables list them, `L`, `E`, `A`, `H`, `C` or `S`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "L" => Ok(Self::Living),
+            "E" => Ok(Self::Extinct),
+            "A" => Ok(Self::Ancient),
+            "H" => Ok(Self::Historical),
+            "C" => Ok(Self::Constructed),
+            "S" => Ok(Self::Special),
+            _ => Err(format!("Unknown language type '{}'", s)),
+        }
+    }
+}
+
+impl LanguageType {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Living => "living",
+            Self::Extinct => "extinct",
+            Self::Ancient => "ancient",
+            Self::Historical => "historical",
+            Self::Constructed => "constructed",
+            Self::Special => "special",
+        }
+    }
+}
+
+/// Standing of a language in a region, from the strongest to the weakest
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[serde(rename_all = "snake_case")]
+pub enum LanguageStatus {
+    /// Only language in the official column of the region
+    Official,
+    /// One of several languages in the official column of the region, such as Finnish and Swedish in Finland
+    CoOfficial,
+    /// National language without official status
+    National,
+    /// Official or recognized in a part of the region
+    Regional,
+    /// Recognized language of a minority
+    Minority,
+}
+
+impl Display for LanguageStatus {
+    fn fmt(&self, f: &mut Formatter) -> Formatted {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl LanguageStatus {
+    /// Official alone or along with others
+    pub fn is_official(&self) -> bool {
+        matches!(self, Self::Official | Self::CoOfficial)
+    }
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Official => "official",
+            Self::CoOfficial => "co_official",
+            Self::National => "national",
+            Self::Regional => "regional",
+            Self::Minority => "minority",
+        }
+    }
+}
+
+/// Region a language is spoken in, and the status of the language there
+#[derive(Debug, Clone, Serialize, JsonSchema, PartialEq, Eq)]
+pub struct LanguageRegion {
+    pub region: Identifier,
+    /// Unknown for languages read from data sets written before statuses were recorded
+    pub status: Option<LanguageStatus>,
+}
+
+impl<'de> Deserialize<'de> for LanguageRegion {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error> where D: Deserializer<'de> {
+        // Data sets written before statuses were recorded list the bare regions
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Stored {
+            Region(Identifier),
+            Link {
+                region: Identifier,
+                #[serde(default)]
+                status: Option<LanguageStatus>,
+            },
+        }
+
+        Ok(match Stored::deserialize(deserializer)? {
+            Stored::Region(region) => Self { region, status: None },
+            Stored::Link { region, status } => Self { region, status },
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Language {
     pub name_short: String,
     pub name_long: String,
     pub iso639: Iso639,
+    /// Regions the language is spoken in, in the order of their identifiers
     #[serde(default)]
-    pub regions: Vec<Identifier>,
+    pub regions: Vec<LanguageRegion>,
+    /// Only known for languages read from the SIL code tables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<LanguageScope>,
+    /// Only known for languages read from the SIL code tables
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub language_type: Option<LanguageType>,
+    /// ISO 639-3 code of the macrolanguage this language belongs to, `zho` for `cmn`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub macrolanguage: Option<Identifier>,
 }
 
 impl Display for Language {
@@ -51,81 +289,105 @@ impl Display for Language {
 }
 
 impl Language {
-    pub fn new(name_short: String, name_long: String, iso639: Iso639, regions: Option<Vec<Identifier>>) -> Self {
+    /// Files of the SIL code tables `read_sil` reads from its directory
+    pub const SIL_TABLES: [&'static str; 2] = [CODE_TABLE, MACROLANGUAGES];
+
+    pub fn new(name_short: String, name_long: String, iso639: Iso639, regions: Option<Vec<LanguageRegion>>) -> Self {
         Self {
             name_short,
             name_long,
             iso639,
-            regions: match regions {
-                Some(r) => r,
-                None => Vec::new(),
-            }
+            regions: regions.unwrap_or_default(),
+            scope: None,
+            language_type: None,
+            macrolanguage: None,
         }
     }
-    pub fn from_html(html: &Html) -> Result<BTreeMap<Identifier, Self>> {
-        let mut cols = HashMap::new();
-        cols.insert(0, Some(Select::Matching("a")));
-        cols.insert(1, Some(Select::Matching("a")));
-        cols.insert(2, Some(Select::Matching("code")));
-        cols.insert(3, Some(Select::Matching("code")));
-        cols.insert(4, Some(Select::Matching("code")));
-        cols.insert(5, None);
-
-        let collect = Include::Some { th_count: 6, td_map: cols };
+    /// Individual languages of a macrolanguage, such as `cmn` and `yue` of `zho`
+    pub fn members<'a>(languages: &'a BTreeMap<Identifier, Self>, macrolanguage: &'a Identifier) -> impl Iterator<Item = &'a Identifier> {
+        languages.iter()
+            .filter(move |(_, l)|l.macrolanguage.as_ref() == Some(macrolanguage))
+            .map(|(id, _)|id)
+    }
+    /// Adds a region the language is spoken in, the strongest status is kept when the language is listed more than once
+    pub fn add_region(&mut self, region: &Identifier, status: LanguageStatus) {
+        match self.regions.iter_mut().find(|r|&r.region == region) {
+            Some(r) => r.status = Some(r.status.map_or(status, |s|s.min(status))),
+            None => self.regions.push(LanguageRegion { region: region.to_owned(), status: Some(status) }),
+        }
+    }
+    pub fn region(&self, region: &Identifier) -> Option<&LanguageRegion> {
+        self.regions.iter().find(|r|&r.region == region)
+    }
+    pub fn is_spoken_in(&self, region: &Identifier) -> bool {
+        self.region(region).is_some()
+    }
+    /// Official in the region, regions of unknown status count as they were read from the official and regional columns alike
+    pub fn is_official_in(&self, region: &Identifier) -> bool {
+        self.region(region).is_some_and(|r|r.status.is_none_or(|s|s.is_official()))
+    }
+    pub fn from_html(html: &Html) -> Result<(BTreeMap<Identifier, Self>, Report)> {
+        let collect = Include::new()
+            .column("ISO language name", Select::Matching("a"))
+            .column("639-1", Select::Matching("a"))
+            .column("639-2/T", Select::Matching("code"))
+            .column("639-2/B", Select::Matching("code"))
+            .column("639-3", Select::Matching("code"));
         let mut items = BTreeMap::new();
+        let mut report = Report::default();
 
-        for m in map_from_table_data(html, collect, None)? {
+        for m in map_from_table_data(html, DATASET, collect, None, &mut report)? {
             // Name from the link title and text
-            let (name_short, name_long) = match m.get(&0).unwrap() {
+            let (name_short, name_long) = match m.get("ISO language name").unwrap() {
                 Found::Children(c) => c.iter()
                     .find_map(|e|link_title_if("/wiki/", *e)
                         .and_then(|n|link_text_if("/wiki/", *e)
-                        .and_then(|t|Some((t.trim().to_string(), n))
+                        .map(|t|(t.trim().to_string(), n)
                     ))
                 )
-                .ok_or(anyhow!("Expected to find a link with language name"))?,
-                _ => bail!("Expected elements for language name column")
+                .ok_or_else(||WikibaseError::row(DATASET, m.index, m["ISO language name"].text(), "Expected to find a link with language name"))?,
+                _ => return Err(WikibaseError::layout(DATASET, "Expected elements for language name column")),
             };
 
             // ISO 639 codes
-            let set1 = match m.get(&1).unwrap() {
+            let set1 = match m.get("639-1").unwrap() {
                 Found::Children(c) => c.iter()
                     .find_map(|e|link_text_if("https://www.loc.gov/standards/iso639-2/", *e))
-                    .ok_or(anyhow!("Expected to find a link with 2 letter language code for {}", name_long))?,
-                _ => bail!("Expected elements for language 2 letter code column")
+                    .ok_or_else(||WikibaseError::row(DATASET, m.index, m["639-1"].text(), format!("Expected to find a link with 2 letter language code for {}", name_long)))?,
+                _ => return Err(WikibaseError::layout(DATASET, "Expected elements for language 2 letter code column")),
             };
 
-            let set2_t: String = match m.get(&2).unwrap() {
+            let set2_t: String = match m.get("639-2/T").unwrap() {
                 Found::Children(c) => c.iter()
                     .next()
-                    .and_then(|e|Some(match e.select(&Selector::parse("b").unwrap()).next() {
+                    .map(|e|match e.select(&Selector::parse("b").unwrap()).next() {
                         Some(b) => b.text().collect(),
                         None => e.text().collect(),
-                    }))
-                    .ok_or(anyhow!("Expected to find a link with 3 letter set2/T language code for {}", name_long))?,
-                _ => bail!("Expected elements for language 3 letter (set 2/T) code column")
+                    })
+                    .ok_or_else(||WikibaseError::row(DATASET, m.index, m["639-2/T"].text(), format!("Expected to find a link with 3 letter set2/T language code for {}", name_long)))?,
+                _ => return Err(WikibaseError::layout(DATASET, "Expected elements for language 3 letter (set 2/T) code column")),
             };
 
-            let set2_b: String = match m.get(&3).unwrap() {
+            let set2_b: String = match m.get("639-2/B").unwrap() {
                 Found::Children(c) => c.iter()
                     .next()
-                    .and_then(|e|Some(match e.select(&Selector::parse("b").unwrap()).next() {
+                    .map(|e|match e.select(&Selector::parse("b").unwrap()).next() {
                         Some(b) => b.text().collect(),
                         None => e.text().collect(),
-                    }))
-                    .ok_or(anyhow!("Expected to find a link with 3 letter set2/B language code for {}", name_long))?,
-                _ => bail!("Expected elements for language 3 letter (set 2/B) code column")
+                    })
+                    .ok_or_else(||WikibaseError::row(DATASET, m.index, m["639-2/B"].text(), format!("Expected to find a link with 3 letter set2/B language code for {}", name_long)))?,
+                _ => return Err(WikibaseError::layout(DATASET, "Expected elements for language 3 letter (set 2/B) code column")),
             };
 
-            let set3: String = match m.get(&4).unwrap() {
+            let set3: String = match m.get("639-3").unwrap() {
                 Found::Children(c) => c.iter()
                     .next()
-                    .and_then(|e|Some(match e.select(&Selector::parse("b").unwrap()).next() {
+                    .map(|e|match e.select(&Selector::parse("b").unwrap()).next() {
                         Some(b) => b.text().collect(),
                         None => e.text().collect(),
-                    }))
-                    .ok_or(anyhow!("Expected to find a link with 3 letter set3 language code for {}", name_long))?,
-                _ => bail!("Expected elements for language 3 letter (set 3) code column")
+                    })
+                    .ok_or_else(||WikibaseError::row(DATASET, m.index, m["639-3"].text(), format!("Expected to find a link with 3 letter set3 language code for {}", name_long)))?,
+                _ => return Err(WikibaseError::layout(DATASET, "Expected elements for language 3 letter (set 3) code column")),
             };
 
             let id = Identifier::new(&set3);
@@ -140,184 +402,241 @@ impl Language {
             items.insert(id, Self::new(name_short, name_long, iso639, None));
         }
 
-        Ok(items)
+        Ok((items, report))
+    }
+    /// Reads `iso-639-3.tab` and `iso-639-3-macrolanguages.tab` of the SIL code tables in `dir` and merges them to the languages
+    pub async fn read_sil<P: AsRef<Path>>(dir: P, languages: &mut BTreeMap<Identifier, Self>) -> Result<Report> {
+        let mut tables = Vec::new();
+
+        for name in [CODE_TABLE, MACROLANGUAGES] {
+            let path = dir.as_ref().join(name);
+            tables.push(read_to_string(&path).await.map_err(|e|WikibaseError::io("Failed to read SIL code table from", &path, e))?);
+        }
+
+        Self::merge_sil(&tables[0], &tables[1], languages)
+    }
+    /// Adds scope, type and macrolanguage of the languages from the SIL code tables of ISO 639-3,
+    /// languages missing from the ISO 639-1 table are added by their reference name
+    pub fn merge_sil(code_table: &str, macrolanguages: &str, languages: &mut BTreeMap<Identifier, Self>) -> Result<Report> {
+        let mut report = Report::default();
+
+        for (row, c) in sil_table(code_table, 0, &CODE_TABLE_COLUMNS, &mut report)? {
+            let (scope, language_type) = match (c[4].parse::<LanguageScope>(), c[5].parse::<LanguageType>()) {
+                (Ok(s), Ok(t)) => (s, t),
+                (Err(e), _) | (_, Err(e)) => {
+                    report.skip(0, row, c.join(" "), e);
+                    continue;
+                },
+            };
+
+            let id = Identifier::new(c[0]);
+
+            let language = languages.entry(id).or_insert_with(||{
+                let iso639 = Iso639 {
+                    set1: c[3].to_string(),
+                    set2_t: c[2].to_string(),
+                    set2_b: c[1].to_string(),
+                    set3: c[0].to_string(),
+                };
+
+                Self::new(c[6].to_string(), c[6].to_string(), iso639, None)
+            });
+
+            language.scope = Some(scope);
+            language.language_type = Some(language_type);
+        }
+
+        for (row, c) in sil_table(macrolanguages, 1, &MACROLANGUAGE_COLUMNS, &mut report)? {
+            // Retired codes are only listed for reference
+            if c[2] != "A" {
+                debug!("Skipping retired member {} of macrolanguage {}", c[1], c[0]);
+                continue;
+            }
+
+            let macrolanguage = Identifier::new(c[0]);
+
+            if !languages.contains_key(&macrolanguage) {
+                report.skip(1, row, c.join(" "), "Unknown macrolanguage");
+                continue;
+            }
+
+            match languages.get_mut(&Identifier::new(c[1])) {
+                Some(l) => l.macrolanguage = Some(macrolanguage),
+                None => report.skip(1, row, c.join(" "), "Unknown language"),
+            }
+        }
+
+        Ok(report)
+    }
+    /// Matcher of the short and long names of the languages, and of the aliases of the ones known by other names
+    pub fn matcher(languages: &BTreeMap<Identifier, Self>) -> NameMatcher {
+        let mut matcher = NameMatcher::new();
+
+        for (id, l) in languages {
+            matcher.insert(&l.name_short, id);
+            matcher.insert(&l.name_long, id);
+        }
+
+        for (alias, code) in ALIASES {
+            let id = Identifier::new(code);
+
+            if languages.contains_key(&id) {
+                matcher.insert(alias, &id);
+            }
+        }
+
+        matcher
     }
     pub fn zones_from_html(
         html: &Html,
         countries: &BTreeMap<Identifier, Vec<String>>,
         regions: &BTreeMap<Identifier, Region>,
         languages: &mut BTreeMap<Identifier, Self>
-    ) -> Result<()> {
-        let mut cols = HashMap::new();
-        cols.insert(0, Some(Select::Matching("a")));
-        cols.insert(1, Some(Select::TdElement));
-        cols.insert(2, Some(Select::TdElement));
-        cols.insert(3, None);
-        cols.insert(4, None);
-        cols.insert(5, None);
-
-        let collect = Include::Some { th_count: 6, td_map: cols };
-
-        for m in map_from_table_data(html, collect, None)? {
+    ) -> Result<Report> {
+        let collect = Include::new()
+            .column_matching("region", "(?i)^country", Select::Matching("a"))
+            .column("Official language", Select::TdElement)
+            .column("Regional language", Select::TdElement)
+            .column("Minority language", Select::TdElement)
+            .column("National language", Select::TdElement);
+
+        let matcher = Self::matcher(languages);
+        let mut report = Report::default();
+
+        'rows: for m in map_from_table_data(html, DATASET, collect, None, &mut report)? {
             // Read region where languages from next columns are used
-            let (reg_title, reg_text) = match m.get(&0) {
+            let (reg_title, reg_text) = match m.get("region") {
                 Some(i) => match i {
                     Found::Children(v) => link_title_and_text_opt_if("/wiki/", v),
                     _ => {
-                        warn!("Expected TD element children for language region column");
+                        report.skip_row(&m, m["region"].text(), "No region link");
                         continue;
                     },
                 },
-                None => bail!("Expected language country column to have at least one element"),
+                None => return Err(WikibaseError::layout(DATASET, "Expected language country column to have at least one element")),
             };
 
             debug!("Processing language of {:?} ({:?})", reg_title, reg_text);
 
+            if reg_title.is_none() && reg_text.is_none() {
+                report.skip_row(&m, m["region"].text(), "No country name");
+                continue;
+            }
+
             // Find the country in the map of regions
-            let (iso_id, region) = match region_by_opt(regions, Some(countries), reg_title.as_ref(), reg_text.as_ref()) {
+            let (iso_id, region) = match region_by_opt(DATASET, m.index, regions, Some(countries), reg_title.as_ref(), reg_text.as_ref()) {
                 Ok(c) => c,
                 Err(e) => {
-                    warn!("Skipping language region {:?} / {:?}: {}", reg_title, reg_text, e);
+                    debug!("Skipping language region {:?} / {:?}: {}", reg_title, reg_text, e);
+                    report.skip_row(&m, m["region"].text(), "Country not found");
                     continue;
                 }
             };
 
             debug!("Found region {} from language zones", region);
 
-            // Read official language column which might hold one or many:
+            let mut found = 0;
+
+            // Each language column might hold one or many:
             // - link to language (internal wikipedia link)
             // - plain text language name inside <td> element
             // - plain text or wikipedia link with language name inside <li> element
-            let official = match m.get(&1) {
-                Some(i) => match i {
-                    Found::Parent(e) => e,
-                    _ => {
-                        warn!("Expected TD element children for official language column");
-                        continue;
+            for (column, status) in COLUMNS {
+                let cell = match m.get(column) {
+                    Some(Found::Parent(e)) => e,
+                    Some(i) => {
+                        report.skip_row(&m, i.text(), format!("No {} cell", column.to_lowercase()));
+                        continue 'rows;
                     },
-                },
-                None => {
-                    warn!("Expected official language column to have at least one element");
-                    continue;
-                },
-            };
-
-            // Process official language cell data
-            if let Err(e) = process_td_cell(official, languages, &iso_id) {
-                warn!("Skipping official languages for {}: {}", iso_id, e);
-                continue;
-            }
-
-            let regional = match m.get(&2) {
-                Some(i) => match i {
-                    Found::Parent(e) => e,
-                    _ => {
-                        warn!("Expected TD element children for regional language column");
-                        continue;
+                    None => {
+                        report.skip_row(&m, "", format!("No {} cell", column.to_lowercase()));
+                        continue 'rows;
                     },
-                },
-                None => {
-                    warn!("Expected regional language column to have at least one element");
-                    continue;
-                },
-            };
+                };
 
-            // Process regional language cell data
-            if let Err(e) = process_td_cell(regional, languages, &iso_id) {
-                warn!("Skipping regional languages for {}: {}", iso_id, e);
-                continue;
-            }
-        }
-    
-        Ok(())
-    }
-}
+                let named = languages_in_cell(cell, &matcher);
 
-fn process_td_cell(td_e: &ElementRef, languages: &mut BTreeMap<Identifier, Language>, region: &Identifier) -> Result<()> {
-    let mut items = Vec::new();
+                for n in &named {
+                    report.match_row(&m, &n.phrase, &n.id);
 
-    // Test if we have a list of languages
-    let li_sel = Selector::parse("li").unwrap();
-    
-    for li in td_e.select(&li_sel) {
-        if let Some(l) = link_text_if("/wiki/", li) {
-            items.push(l);
+                    if let Some(l) = languages.get_mut(&n.id) {
+                        info!("Added {} to language {} as {}", iso_id, &l.name_short, status);
+                        l.add_region(&iso_id, status);
+                    }
+                }
 
-            // Try this too
-            if let Some(l) = link_title_if("/wiki/", li) {
-                items.push(l);
+                found += named.len();
             }
 
-            continue;
+            if found == 0 {
+                report.skip_row(&m, m["Official language"].text(), "No known language");
+            }
         }
 
-        el_text_splitter(&li, &mut items);
+        share_official_status(languages);
+        Ok(report)
     }
+}
 
-    // Proceed by checking if we have languages as links in a string
-    let a_sel = Selector::parse("a").unwrap();
+// Languages official in the same region along with others are co-official there, however many rows listed them
+fn share_official_status(languages: &mut BTreeMap<Identifier, Language>) {
+    let mut official = BTreeMap::<Identifier, usize>::new();
 
-    for a in td_e.select(&a_sel) {
-        if let Some(l) = link_text_if("/wiki/", a) {
-            items.push(l);
+    for r in languages.values().flat_map(|l|&l.regions).filter(|r|r.status == Some(LanguageStatus::Official)) {
+        *official.entry(r.region.to_owned()).or_default() += 1;
+    }
 
-            // Try this too
-            if let Some(l) = link_title_if("/wiki/", a) {
-                items.push(l);
-            }
+    for r in languages.values_mut().flat_map(|l|&mut l.regions) {
+        if r.status == Some(LanguageStatus::Official) && official.get(&r.region).is_some_and(|c|*c > 1) {
+            r.status = Some(LanguageStatus::CoOfficial);
         }
     }
+}
 
-    // Might be a flat language name on the element or a novel of some sort containing language names here and there.
-    // Lets just split from spaces and treat every word as a potential language.
-    el_text_splitter(td_e, &mut items);
+// Languages named in the cell, the first phrase naming each language
+fn languages_in_cell(td_e: &ElementRef, matcher: &NameMatcher) -> Vec<NameMatch> {
+    // Titles of the links name the language in full, text of the cell might only have a part of it
+    let a_sel = Selector::parse("a").unwrap();
 
-    // Compare found language names (or irrelevant crap) to known languages
-    items.sort();
-    items.dedup();
+    let mut texts = td_e.select(&a_sel)
+        .filter_map(|a|link_title_if("/wiki/", a))
+        .collect::<Vec<String>>();
 
-    for i in items.iter() {
-        let lcl = i.to_lowercase();
-        
-        if let Some(l) = languages.values_mut().find(|l|l.name_short.to_lowercase() == lcl || l.name_long.to_lowercase() == lcl) {
-            if l.regions.contains(&region) {
-                debug!("Language {} already has region {}", i, region);
-                continue;
-            }
+    texts.push(td_e.text().collect::<Vec<&str>>().join(" "));
+
+    let mut found = Vec::<NameMatch>::new();
 
-            info!("Added {} to language {}", region, &l.name_short);
-            l.regions.push(region.to_owned());
+    for m in texts.iter().flat_map(|t|matcher.find(t)) {
+        // Same language might be named by its link and its text
+        if !found.iter().any(|f|f.id == m.id) {
+            found.push(m);
         }
     }
 
-    Ok(())
+    found
 }
 
-fn el_text_splitter(html_el: &ElementRef, items: &mut Vec<String>) {
-    for t in html_el.text() {
-        for s in t.split_whitespace() {
-            // Only words with alphabetic characters are considered
-            let mut word = String::new();
-            
-            for c in s.chars() {
-                if c.is_alphabetic() {
-                    word.push(c);
-                }
-            }
+// Data rows of a tab separated SIL code table with their index, header has to start with given columns
+fn sil_table<'a>(text: &'a str, table: usize, columns: &[&str], report: &mut Report) -> Result<Vec<(usize, Vec<&'a str>)>> {
+    let mut lines = text.trim_start_matches('\u{feff}').lines();
 
-            // Exclude short words
-            if word.len() < 3 {
-                continue;
-            }
+    let header = lines.next().unwrap_or_default().trim_end_matches('\r').split('\t').collect::<Vec<&str>>();
 
-            // See if word is present on the exclude list
-            let s = word.to_lowercase();
+    if !header.starts_with(columns) {
+        return Err(WikibaseError::layout(DATASET, format!("Expected SIL code table with columns {}", columns.join(", "))))
+    }
 
-            if EXCLUDE.contains(&s.as_str()) {
-                continue;
-            }
-            
-            items.push(word);
+    let mut rows = Vec::new();
+
+    for (i, l) in lines.map(|l|l.trim_end_matches('\r')).filter(|l|!l.is_empty()).enumerate() {
+        let cells = l.split('\t').map(|c|c.trim()).collect::<Vec<&str>>();
+
+        // Comment is the only column that might be left out
+        match cells.len() >= columns.len() - 1 && !cells[0].is_empty() {
+            true => rows.push((i, cells)),
+            false => report.skip(table, i, l, "Missing columns"),
         }
     }
-}
\ No newline at end of file
+
+    Ok(rows)
+}
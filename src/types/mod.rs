@@ -6,24 +6,27 @@ mod currency;
 mod calling_codes;
 mod language;
 mod capital;
+mod country;
 
 use std::fmt::{Display, Formatter};
 use std::fmt::Result as Formatted;
 
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use scraper::ElementRef;
 
 pub use sovereign_state::SovereignState;
-pub use region::Region;
+pub use region::{Region, Iso3166_1, Iso3166_2, Tld};
 pub use flag::Flag;
 pub use un_nations::UNMember;
-pub use currency::Currency;
+pub use currency::{Currency, Fraction};
 pub use calling_codes::CallingCode;
-pub use language::Language;
+pub use language::{Language, LanguageRegion, LanguageScope, LanguageStatus, LanguageType, Iso639};
 pub use capital::Capital;
+pub use country::{Country, CountryCurrency, CountryLanguage};
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, Hash, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Hash, Eq, PartialEq, Ord, PartialOrd)]
 pub struct Identifier (
     #[serde(deserialize_with = "Identifier::deserialize", serialize_with = "Identifier::serialize")]
     pub String
@@ -56,7 +59,7 @@ impl Identifier {
     pub fn as_str(&self) -> &str {
         &self.0
     }
-    fn serialize<S>(id: &String, serializer: S) -> Result<S::Ok, S::Error>
+    fn serialize<S>(id: &str, serializer: S) -> Result<S::Ok, S::Error>
     where S: serde::Serializer {
         let lid = id.to_lowercase();
         serializer.serialize_str(&lid)
@@ -76,7 +79,7 @@ fn link_title_if(prefix: &str, elref: ElementRef) -> Option<String> {
         return None
     }
 
-    elref.attr("title").and_then(|s|Some(s.trim().to_string()))
+    elref.attr("title").map(|s|s.trim().to_string())
 }
 
 fn link_text_if(prefix: &str, elref: ElementRef) -> Option<String> {
@@ -1,21 +1,28 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fmt::Result as Formatted;
 
-use anyhow::{anyhow, bail, Result};
 use scraper::Html;
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 use scraper::Selector;
-use tracing::{debug, warn};
+use tracing::debug;
 
+use crate::error::{Result, WikibaseError};
 use crate::map::{Include, Found, map_from_table_data, Select};
+use crate::report::Report;
+use crate::DatasetKind;
 
 use super::{link_text_if, link_title_if, Identifier, SovereignState};
 
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+const DATASET: DatasetKind = DatasetKind::Regions;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Iso3166_1 {
+    #[schemars(regex(pattern = r"^[A-Z]{2}$"))]
     pub a2: String,
+    #[schemars(regex(pattern = r"^[A-Z]{3}$"))]
     pub a3: String,
     pub num: u16,
 }
@@ -29,11 +36,11 @@ impl Display for Iso3166_1 {
 impl Iso3166_1 {
     pub fn new(a2: String, a3: String, num: u16) -> Result<Self> {
         if a2.len() != 2 {
-            bail!("Expected 2 characters for a2, got {}", a2.len());
+            return Err(WikibaseError::validation(DATASET, format!("Expected 2 characters for a2, got {}", a2.len())))
         }
 
         if a3.len() != 3 {
-            bail!("Expected 3 characters for a3, got {}", a3.len());
+            return Err(WikibaseError::validation(DATASET, format!("Expected 3 characters for a3, got {}", a3.len())))
         }
 
         Ok(Self {
@@ -44,7 +51,7 @@ impl Iso3166_1 {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Iso3166_2 (pub String);
 
 impl Display for Iso3166_2 {
@@ -60,12 +67,12 @@ impl Iso3166_2 {
         // Expected: ISO 3166-2:XX
         match clean.starts_with("ISO 3166-2:") && clean.len() == 13 {
             true => Ok(Self(clean)),
-            false => bail!("Expected ISO 3166-2: prefix, got {}", clean),
+            false => Err(WikibaseError::validation(DATASET, format!("Expected ISO 3166-2: prefix, got {}", clean))),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Tld (pub Vec<String>);
 
 impl Display for Tld {
@@ -89,7 +96,7 @@ impl Tld {
             // Expected: .xx
             match clean.starts_with(".") && clean.len() == 3 {
                 true => valid.push(clean),
-                false => bail!("Expected .xx domain tld, got {}", clean),
+                false => return Err(WikibaseError::validation(DATASET, format!("Expected .xx domain tld, got {}", clean))),
             }
         }
 
@@ -97,7 +104,7 @@ impl Tld {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Region {
     pub name: String,
     pub state_name: String,
@@ -126,30 +133,29 @@ impl Region {
             tld
         }
     }
-    pub fn from_html(html: &Html, sovereign_states: &BTreeMap<Identifier, SovereignState>) -> Result<BTreeMap<Identifier, Self>> {
-        let mut cols = HashMap::new();
-        cols.insert(0, Select::Matching("a"));
-        cols.insert(1, Select::Matching("a"));
-        cols.insert(2, Select::TdElement);
-        cols.insert(3, Select::Matching("a > span"));
-        cols.insert(4, Select::Matching("a > span"));
-        cols.insert(5, Select::Matching("a > span"));
-        cols.insert(6, Select::Matching("a"));
-        cols.insert(7, Select::Matching("a"));
-
-        let collect = Include::All { th_count: 9, td_map: cols };
+    pub fn from_html(html: &Html, sovereign_states: &BTreeMap<Identifier, SovereignState>) -> Result<(BTreeMap<Identifier, Self>, Report)> {
+        let collect = Include::new()
+            .column("ISO 3166 name", Select::Matching("a"))
+            .column("Official state name", Select::Matching("a"))
+            .column("Sovereignty", Select::TdElement)
+            .column("A-2", Select::Matching("a > span"))
+            .column("A-3", Select::Matching("a > span"))
+            .column("Num.", Select::Matching("a > span"))
+            .column_matching("ISO 3166-2", "^ISO 3166-2", Select::Matching("a"))
+            .column_matching("ccTLD", "ccTLD", Select::Matching("a"));
         let mut items = BTreeMap::new();
+        let mut report = Report::default();
     
-        for m in map_from_table_data(html, collect, None)? {
-            let name = match m.get(&0).unwrap() {
+        for m in map_from_table_data(html, DATASET, collect, None, &mut report)? {
+            let name = match m.get("ISO 3166 name").unwrap() {
                 Found::Children(c) => c.iter()
                     .find_map(|e|link_title_if("/wiki/", *e))
-                    .ok_or(anyhow!("Failed to read ISO 3166 name"))?,
-                _ => bail!("Expected elements for region name")
+                    .ok_or_else(||WikibaseError::row(DATASET, m.index, m["ISO 3166 name"].text(), "Failed to read ISO 3166 name"))?,
+                _ => return Err(WikibaseError::layout(DATASET, "Expected elements for region name")),
             };
 
             // 3rd column is the charm here, sovereignity defines how to treat the entry
-            let (iso_id, sovereignity, un_member) = match m.get(&2) {
+            let (iso_id, sovereignity, un_member) = match m.get("Sovereignty") {
                 Some(v) => match v {
                     Found::Parent(v) => {
                         // Can be a link with a text 'UN member'
@@ -170,7 +176,7 @@ impl Region {
                                 None => match sovereign_states.iter().find(|(_, i)|i.name_long.to_lowercase() == name.to_lowercase()) {
                                     Some(t) => (t.0.clone(), t.1.clone(), true),
                                     None => {
-                                        warn!("Failed to find ISO 3166 '{}' from provided list of sovereign states", name);
+                                        report.skip_row(&m, m["ISO 3166 name"].text(), "UN member not found from sovereign states");
                                         continue;
                                     }
                                 }
@@ -183,38 +189,38 @@ impl Region {
                                 None => match sovereign_states.iter().find(|(_, i)|i.name_long.to_lowercase() == s.to_lowercase()) {
                                     Some(t) => (t.0.clone(), t.1.clone(), false),
                                     None => {
-                                        warn!("Failed to find ISO 3166 '{}' reference to '{}' from provided list of sovereign states", name, s);
+                                        report.skip_row(&m, m["Sovereignty"].text(), "Sovereign state not found");
                                         continue;
                                     }
                                 }
                             }
                         }
                         else {
-                            warn!("Failed to determine sovereignity for {}, skipping...", name);
+                            report.skip_row(&m, m["Sovereignty"].text(), "Unknown sovereignty");
                             continue;
                         }
                     },
-                    _ => bail!("Expected parent element for sovereignity column for {}", name)
+                    _ => return Err(WikibaseError::layout(DATASET, format!("Expected parent element for sovereignity column for {}", name))),
                 },
-                None => bail!("Failed to read sovereignity column for {}", name)
+                None => return Err(WikibaseError::row(DATASET, m.index, m["Sovereignty"].text(), format!("Failed to read sovereignity column for {}", name)))
             };
 
             debug!("ISO 3166 region {} sovereignity set to {}", iso_id, sovereignity.name_long);
 
             // Get the official ISO 3166 country name from 2nd column
-            let state_name = match m.get(&1).unwrap() {
+            let state_name = match m.get("Official state name").unwrap() {
                 Found::Children(v) => {
                     let first = v.first().and_then(|e|e.text().next());
 
                     match first {
                         Some(s) => s.trim().to_string(),
-                        None => bail!("Failed to read official state name for {}", name)
+                        None => return Err(WikibaseError::row(DATASET, m.index, m["Official state name"].text(), format!("Failed to read official state name for {}", name)))
                     }
                 },
-                _ => bail!("Expected all for iso_3166 state name for {}", name)
+                _ => return Err(WikibaseError::layout(DATASET, format!("Expected all for iso_3166 state name for {}", name))),
             };
 
-            let iso_3166_1_a2 = match m.get(&3).unwrap() {
+            let iso_3166_1_a2 = match m.get("A-2").unwrap() {
                 Found::Children(c) => match c.first() {
                     Some(e) => match e.text().next() {
                         Some(s) => {
@@ -222,17 +228,17 @@ impl Region {
 
                             match val.len() {
                                 2 => val,
-                                _ => bail!("Expected 2 characters for iso_3166_1 a2 ({}), got {}", name, val.len())
+                                _ => return Err(WikibaseError::row(DATASET, m.index, m["A-2"].text(), format!("Expected 2 characters for iso_3166_1 a2 ({}), got {}", name, val.len()))),
                             }
                         },
-                        None => bail!("Expected text for iso_3166_1 a2")
+                        None => return Err(WikibaseError::row(DATASET, m.index, m["A-2"].text(), "Expected text for iso_3166_1 a2"))
                     },
-                    None => bail!("Invalid ISO 3166-1 a2 code for {}", name)
+                    None => return Err(WikibaseError::row(DATASET, m.index, m["A-2"].text(), format!("Invalid ISO 3166-1 a2 code for {}", name)))
                 }
-                _ => bail!("Expected elements for iso_3166_1 two letter code for {}", name)
+                _ => return Err(WikibaseError::layout(DATASET, format!("Expected elements for iso_3166_1 two letter code for {}", name))),
             };
 
-            let iso_3166_1_a3 = match m.get(&4).unwrap() {
+            let iso_3166_1_a3 = match m.get("A-3").unwrap() {
                 Found::Children(c) => match c.first() {
                     Some(e) => match e.text().next() {
                         Some(s) => {
@@ -240,53 +246,55 @@ impl Region {
 
                             match val.len() {
                                 3 => val,
-                                _ => bail!("Expected 3 characters for iso_3166_1 a3 ({}), got {}", name, val.len())
+                                _ => return Err(WikibaseError::row(DATASET, m.index, m["A-3"].text(), format!("Expected 3 characters for iso_3166_1 a3 ({}), got {}", name, val.len()))),
                             }
                         },
-                        None => bail!("Expected text for iso_3166_1 a3 for {}", name)
+                        None => return Err(WikibaseError::row(DATASET, m.index, m["A-3"].text(), format!("Expected text for iso_3166_1 a3 for {}", name)))
                     },
-                    None => bail!("Invalid ISO 3166-1 a3 code for {}", name)
+                    None => return Err(WikibaseError::row(DATASET, m.index, m["A-3"].text(), format!("Invalid ISO 3166-1 a3 code for {}", name)))
                 }
-                _ => bail!("Expected elements for iso_3166_1 3 letter code for {}", name)
+                _ => return Err(WikibaseError::layout(DATASET, format!("Expected elements for iso_3166_1 3 letter code for {}", name))),
             };
 
-            let iso_3166_1_num = match m.get(&5).unwrap() {
+            let iso_3166_1_num = match m.get("Num.").unwrap() {
                 Found::Children(c) => match c.first() {
                     Some(e) => match e.text().next() {
                         Some(s) => {
-                            let val = s.trim().parse::<u16>()?;
+                            let val = s.trim().parse::<u16>().map_err(|e|
+                                WikibaseError::row(DATASET, m.index, s.trim(), format!("Invalid ISO 3166-1 num code for {}: {}", name, e))
+                            )?;
 
                             match val {
                                 0..=999 => val,
-                                _ => bail!("Expected 3 digit number for iso_3166_1 num ({}), got {}", name, val)
+                                _ => return Err(WikibaseError::row(DATASET, m.index, m["Num."].text(), format!("Expected 3 digit number for iso_3166_1 num ({}), got {}", name, val))),
                             }
                         },
-                        None => bail!("Expected text for iso_3166_1 num for {}", name)
+                        None => return Err(WikibaseError::row(DATASET, m.index, m["Num."].text(), format!("Expected text for iso_3166_1 num for {}", name)))
                     },
-                    None => bail!("Invalid ISO 3166-1 num code for {}", name)
+                    None => return Err(WikibaseError::row(DATASET, m.index, m["Num."].text(), format!("Invalid ISO 3166-1 num code for {}", name)))
                 }
-                _ => bail!("Expected elements for iso_3166_1 numeric value for {}", name)
+                _ => return Err(WikibaseError::layout(DATASET, format!("Expected elements for iso_3166_1 numeric value for {}", name))),
             };
 
-            let iso_3166_2 = match m.get(&6).unwrap() {
+            let iso_3166_2 = match m.get("ISO 3166-2").unwrap() {
                 Found::Children(c) => c.iter()
                     .find_map(|e|link_title_if("/wiki/", *e))
-                    .ok_or(anyhow!("Failed to read ISO 3166-2 column for {}", name))?,
-                _ => bail!("Expected elements for iso_3166_2 value for {}", name)
+                    .ok_or_else(||WikibaseError::row(DATASET, m.index, m["ISO 3166-2"].text(), format!("Failed to read ISO 3166-2 column for {}", name)))?,
+                _ => return Err(WikibaseError::layout(DATASET, format!("Expected elements for iso_3166_2 value for {}", name))),
             };
 
-            let tld = match m.get(&7).unwrap() {
+            let tld = match m.get("ccTLD").unwrap() {
                 Found::Children(c) => c.iter()
                     .filter_map(|e|link_title_if("/wiki/", *e))
                     .collect(),
-                _ => bail!("Expected elements for tld value for {}", name)
+                _ => return Err(WikibaseError::layout(DATASET, format!("Expected elements for tld value for {}", name))),
             };
 
             // Create identifier from 2 letter code
             let id = Identifier::new(&iso_3166_1_a2);
 
             if items.contains_key(&id) {
-                bail!("Duplicate entry for {} / {}", iso_3166_1_a2, name);
+                return Err(WikibaseError::validation(DATASET, format!("Duplicate entry for {} / {}", iso_3166_1_a2, name)))
             }
     
             items.insert(
@@ -303,11 +311,14 @@ impl Region {
             );
         }
     
-        Ok(items)
+        Ok((items, report))
     }
 }
 
+/// Region named by either of the names, `row` is the index of the table row the names were read from
 pub fn region_by_opt(
+    dataset: DatasetKind,
+    row: usize,
     regions: &BTreeMap<Identifier, Region>,
     countries: Option<&BTreeMap<Identifier, Vec<String>>>,
     first: Option<&String>,
@@ -321,20 +332,12 @@ pub fn region_by_opt(
         return Ok(t)
     }
 
-    if first.is_some() && second.is_some() {
-        bail!("ISO 3166 country not found from provided list with name {} or {}", first.unwrap(), second.unwrap())
-    }
-
-    else if first.is_some() {
-        bail!("ISO 3166 country not found from provided list with name {}", first.unwrap())
-    }
-
-    else if second.is_some() {
-        bail!("ISO 3166 country not found from provided list with name {}", second.unwrap())
-    }
+    let err = |text: String, reason: &str|WikibaseError::row(dataset, row, text, reason);
 
-    else {
-        panic!("Hard to find a ISO 3166 country if you don't provide search terms")
+    match (first, second) {
+        (Some(f), Some(s)) => Err(err(format!("{} / {}", f, s), "ISO 3166 country not found from provided list")),
+        (Some(n), None) | (None, Some(n)) => Err(err(n.to_string(), "ISO 3166 country not found from provided list")),
+        (None, None) => Err(err(String::new(), "No country name to look up")),
     }
 }
 
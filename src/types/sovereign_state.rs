@@ -1,18 +1,23 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
 use std::fmt::Result as Formatted;
-use anyhow::{anyhow, bail, Result};
 use scraper::Html;
 use serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
 use tracing::{debug, warn};
 
+use crate::error::{Result, WikibaseError};
 use crate::map::{Include, Found, map_from_table_data, Select};
+use crate::report::Report;
 use crate::types::link_text_if;
+use crate::DatasetKind;
 
 use super::{link_title_if, Identifier, UNMember};
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+const DATASET: DatasetKind = DatasetKind::SovereignStates;
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Eq, PartialEq, Ord, PartialOrd)]
 pub struct SovereignState {
     pub name_short: String,
     pub name_long: String,
@@ -35,26 +40,24 @@ impl SovereignState {
             disputed
         }
     }
-    pub fn from_html(html: &Html, un_nations: &Vec<UNMember>, countries: &BTreeMap<Identifier, Vec<String>>) -> Result<BTreeMap<Identifier, Self>> {
-        let mut cols = HashMap::new();
-        cols.insert(0, Some(Select::Matching("a")));
-        cols.insert(1, Some(Select::InnerAsText));
-        cols.insert(2, Some(Select::InnerAsText));
-        cols.insert(3, None);
-
-        let collect = Include::Some { th_count: 4, td_map: cols };
+    pub fn from_html(html: &Html, un_nations: &[UNMember], countries: &BTreeMap<Identifier, Vec<String>>) -> Result<(BTreeMap<Identifier, Self>, Report)> {
+        let collect = Include::new()
+            .column_matching("name", "(?i)names$", Select::Matching("a"))
+            .column_matching("membership", "(?i)^membership", Select::InnerAsText)
+            .column("Sovereignty dispute", Select::InnerAsText);
         let mut items = BTreeMap::new();
+        let mut report = Report::default();
     
-        for m in map_from_table_data(html, collect, None)? {
-            let (name,  name_long) = match m.get(&0).unwrap() {
+        for m in map_from_table_data(html, DATASET, collect, None, &mut report)? {
+            let (name,  name_long) = match m.get("name").unwrap() {
                 Found::Children(c) => c.iter()
                     .find_map(|e|link_title_if("/wiki/", *e)
                         .and_then(|n|link_text_if("/wiki/", *e)
-                        .and_then(|t|Some((t.trim().to_string(), n))
+                        .map(|t|(t.trim().to_string(), n)
                     ))
                 )
-                .ok_or(anyhow!("Expected to find a link with country name"))?,
-                _ => bail!("Expected elements for country name column")
+                .ok_or_else(||WikibaseError::row(DATASET, m.index, m["name"].text(), "Expected to find a link with country name"))?,
+                _ => return Err(WikibaseError::layout(DATASET, "Expected elements for country name column"))
             };
 
             // Take country identifier from UN members list (2 letter ISO 3166) if it exists
@@ -87,11 +90,11 @@ impl SovereignState {
             // Remove everything that comes after comma. Leave the long version intact.
             let name_short = name.split(",").next().unwrap_or(&name).to_string();
     
-            let member = match m.get(&1).unwrap() {
+            let member = match m.get("membership").unwrap() {
                 Found::InnerText(c) => c.iter().any(|s|
                     s.to_lowercase().contains("un member state") && s.len() < 25
                 ),
-                _ => bail!("Expected content")
+                _ => return Err(WikibaseError::layout(DATASET, "Expected text for UN membership column"))
             };
 
             if !un_nations.is_empty() {
@@ -110,9 +113,9 @@ impl SovereignState {
                 }
             }
     
-            let dispute = match m.get(&2).unwrap() {
+            let dispute = match m.get("Sovereignty dispute").unwrap() {
                 Found::InnerText(c) => !c.iter().any(|s|s.to_lowercase().contains("none")),
-                _ => bail!("Expected content")
+                _ => return Err(WikibaseError::layout(DATASET, "Expected text for sovereignty dispute column"))
             };
     
             if member && dispute {
@@ -120,14 +123,14 @@ impl SovereignState {
             }
 
             if items.contains_key(&id) {
-                warn!("{} already exists, skipping", name_long);
+                report.skip_row(&m, m["name"].text(), "Duplicate entry");
                 continue;
             }
     
             items.insert(id, SovereignState::new(name_short, name_long, member, dispute));
         }
     
-        Ok(items)
+        Ok((items, report))
     }
 }
 
@@ -3,14 +3,18 @@ use std::fmt::{Display, Formatter};
 use std::fmt::Result as Formatted;
 
 use serde::{Deserialize, Serialize};
-use anyhow::{Result, bail};
-use scraper::Selector;
+use schemars::JsonSchema;
+use scraper::{Html, Selector};
 use tracing::warn;
 
+use crate::error::{Result, WikibaseError};
+use crate::fetch::Fetcher;
+use crate::DatasetKind;
+
 use super::Identifier;
 
 
-#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Eq, PartialEq, Ord, PartialOrd)]
 pub struct UNMember {
     pub name: String,
     pub iso_3166: Option<Identifier>,
@@ -29,8 +33,12 @@ impl UNMember {
     pub fn new(name: String, code: Option<Identifier>) -> Self {
         Self { name, iso_3166: code }
     }
-    pub async fn fetch_un_nations(url: &str, countries: &BTreeMap<Identifier, Vec<String>>) -> Result<Vec<Self>> {
-        let html = crate::fetch::get_html(url).await?;
+    pub async fn fetch_un_nations(fetcher: &Fetcher, url: &str, countries: &BTreeMap<Identifier, Vec<String>>) -> Result<Vec<Self>> {
+        let html = fetcher.get_html(url).await?;
+
+        Self::from_html(&html, countries)
+    }
+    pub fn from_html(html: &Html, countries: &BTreeMap<Identifier, Vec<String>>) -> Result<Vec<Self>> {
         let selector = Selector::parse(".country div>h2").unwrap();
         let mut nations = Vec::new();
     
@@ -48,7 +56,7 @@ impl UNMember {
         }
     
         if nations.is_empty() {
-            bail!("Failed to fetch UN member states");
+            return Err(WikibaseError::layout(DatasetKind::UnNations, "No UN member states found"))
         }
     
         Ok(nations)
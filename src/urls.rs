@@ -0,0 +1,12 @@
+//! Documents the data sets are scraped from
+
+pub const UN_NATIONS: &str = "https://www.un.org/en/about-us/member-states";
+pub const SOVEREIGN_STATES: &str = "https://en.wikipedia.org/wiki/List_of_sovereign_states";
+pub const FLAGS: &str = "https://en.wikipedia.org/wiki/Gallery_of_sovereign_state_flags";
+pub const ISO_3166: &str = "https://en.wikipedia.org/wiki/List_of_ISO_3166_country_codes";
+pub const CURRENCIES: &str = "https://en.wikipedia.org/wiki/List_of_circulating_currencies";
+pub const EMOJIS: &str = "https://en.wikipedia.org/wiki/Regional_indicator_symbol";
+pub const CALLING_CODES: &str = "https://en.wikipedia.org/wiki/List_of_country_calling_codes";
+pub const LANG_CODES_ISO_639: &str = "https://en.wikipedia.org/wiki/List_of_ISO_639_language_codes";
+pub const LANG_ZONES: &str = "https://en.wikipedia.org/wiki/List_of_official_languages_by_country_and_territory";
+pub const CAPITALS: &str = "https://en.wikipedia.org/wiki/List_of_countries_and_dependencies_and_their_capitals_in_native_languages";
@@ -0,0 +1,73 @@
+mod common;
+
+use common::{assert_generated, expected};
+use wikibase::export::{python, typescript};
+use wikibase::Dataset;
+
+
+fn dataset() -> Dataset {
+    Dataset {
+        regions: expected("regions"),
+        calling_codes: expected("calling_codes"),
+        capitals: expected("capitals"),
+        ..Dataset::default()
+    }
+}
+
+// Names of the types declared in `src`, `declaration` gives the line without what precedes the name
+fn declared<'a, F>(src: &'a str, declaration: F) -> Vec<&'a str> where F: Fn(&'a str) -> Option<&'a str> {
+    let mut names = src.lines()
+        .filter_map(declaration)
+        .filter_map(|l|l.split([' ', '(', ':']).next())
+        .collect::<Vec<&str>>();
+
+    names.sort();
+    names
+}
+
+#[test]
+fn typescript_declarations_are_generated() {
+    assert_generated("types.d.ts", &typescript::declarations());
+}
+
+#[test]
+fn python_declarations_are_generated() {
+    assert_generated("types.py", &python::declarations());
+}
+
+#[test]
+fn python_declarations_fall_back_to_typing_extensions() {
+    let py = python::declarations();
+
+    // NotRequired is only in typing since Python 3.11
+    assert!(py.contains("try:\n    from typing import NotRequired, TypeAlias, TypedDict\nexcept ImportError:\n    from typing_extensions import NotRequired, TypeAlias, TypedDict\n"), "{}", py);
+    assert!(py.lines().filter(|l|l.starts_with("from typing import")).all(|l|!l.contains("NotRequired")));
+}
+
+#[test]
+fn languages_declare_the_same_types() {
+    let ts = typescript::declarations();
+    let py = python::declarations();
+
+    let ts = declared(&ts, |l|l.strip_prefix("export interface ").or(l.strip_prefix("export type ")));
+    let py = declared(&py, |l|l.strip_prefix("class ").or(l.contains(": TypeAlias = ").then_some(l)));
+
+    assert!(ts.contains(&"Region") && ts.contains(&"Countries"), "{:?}", ts);
+    assert_eq!(ts, py);
+}
+
+#[test]
+fn data_modules_hold_the_data_sets() {
+    let ts = typescript::data_module(&dataset()).unwrap();
+
+    assert!(ts.contains("import type { UnNations, SovereignStates, Regions,"), "{}", ts);
+    assert!(ts.contains("export const callingCodes: CallingCodes = {\n  \"ag\": {\n    \"area_codes\": [\n      \"268\"\n    ],\n    \"country_code\": 1,"), "{}", ts);
+    assert!(ts.contains("export const unNations: UnNations = [];"));
+
+    let py = python::generate(&dataset()).unwrap();
+
+    assert!(py.contains("CALLING_CODES: CallingCodes = {\n    \"ag\": {\n        \"area_codes\": [\n            \"268\",\n        ],\n        \"country_code\": 1,"), "{}", py);
+    assert!(py.contains("\"fi\": {\n        \"endonyms\": [\n            \"Helsingfors\",\n        ],\n        \"name\": \"Helsinki\",\n    },"), "{}", py);
+    assert!(py.contains("\"un_member\": False,"));
+    assert!(py.contains("UN_NATIONS: UnNations = []"));
+}
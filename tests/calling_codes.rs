@@ -0,0 +1,53 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use common::expected;
+use wikibase::types::CallingCode;
+use wikibase::Identifier;
+
+
+#[test]
+fn codes_are_read_to_parts() {
+    let code = "1 (809, 829, 849)".parse::<CallingCode>().unwrap();
+
+    assert_eq!(code.country_code, 1);
+    assert_eq!(code.area_codes, ["809", "829", "849"]);
+    assert_eq!(code.zone, 1);
+    assert_eq!(code.prefixes(), ["1809", "1829", "1849"]);
+    assert_eq!(code.to_string(), "1 (809, 829, 849)");
+
+    let code = "358".parse::<CallingCode>().unwrap();
+    assert_eq!((code.country_code, code.zone, code.prefixes()), (358, 3, vec!["358".to_string()]));
+
+    for invalid in ["", "1 (268", "1234", "44 (a)", "+358"] {
+        assert!(invalid.parse::<CallingCode>().is_err(), "{} was read", invalid);
+    }
+}
+
+#[test]
+fn codes_written_as_text_are_read() {
+    let codes = serde_json::from_str::<BTreeMap<Identifier, CallingCode>>(r#"{
+        "ag": "1 (268)",
+        "fi": { "country_code": 358, "zone": 3 }
+    }"#).unwrap();
+
+    assert_eq!(codes[&Identifier::new("ag")], CallingCode::new(1, vec!["268".to_string()]));
+    assert_eq!(codes[&Identifier::new("fi")], CallingCode::new(358, Vec::new()));
+}
+
+#[test]
+fn regions_are_found_by_longest_prefix() {
+    let codes = expected::<BTreeMap<Identifier, CallingCode>>("calling_codes");
+    let regions = |number: &str|CallingCode::regions_of(&codes, number).into_iter().map(|i|i.as_str().to_string()).collect::<Vec<String>>();
+
+    assert_eq!(regions("+358 18 123 456"), ["ax"]);
+    assert_eq!(regions("+358 9 123 456"), ["fi"]);
+    assert_eq!(regions("+1 268 123 4567"), ["ag"]);
+    assert_eq!(regions("+1 787"), ["pr"]);
+    assert_eq!(regions("+1 212 555 0100"), ["us"]);
+    assert_eq!(regions("+46 8 123"), ["se"]);
+    // Too short to tell Puerto Rico from the United States
+    assert_eq!(regions("+1 78"), ["pr", "us"]);
+    assert!(regions("+999").is_empty());
+}
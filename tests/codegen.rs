@@ -0,0 +1,78 @@
+mod common;
+
+use common::{assert_generated, expected};
+use wikibase::export::rust;
+use wikibase::Dataset;
+
+// Module generated from the fixtures, compiled along with the tests
+#[allow(dead_code)]
+mod countries {
+    include!("fixtures/generated/countries.rs");
+}
+
+use countries::{currency, language, CallingCode, Country};
+
+
+fn dataset() -> Dataset {
+    Dataset {
+        sovereign_states: expected("sovereign_states"),
+        regions: expected("regions"),
+        emojis: expected("emojis"),
+        currencies: expected("currencies"),
+        calling_codes: expected("calling_codes"),
+        languages: expected("language_zones"),
+        capitals: expected("capitals"),
+        ..Dataset::default()
+    }
+}
+
+#[test]
+fn rust_module_is_generated() {
+    assert_generated("countries.rs", &rust::generate(&dataset()));
+}
+
+#[test]
+fn lookups_are_const() {
+    const FINLAND: Country = match Country::from_alpha2("FI") {
+        Some(c) => c,
+        None => panic!("Finland not found"),
+    };
+    const ALPHA3: &str = FINLAND.alpha3();
+
+    assert_eq!(ALPHA3, "FIN");
+    assert_eq!(FINLAND.numeric(), 246);
+    assert_eq!(FINLAND.calling_code(), Some(CallingCode { country_code: 358, area_codes: &[] }));
+    assert_eq!(FINLAND.emoji(), Some("🇫🇮"));
+    assert_eq!(FINLAND.capital(), Some("Helsinki"));
+}
+
+#[test]
+fn calling_codes_keep_their_parts() {
+    let aland = Country::AX.calling_code().unwrap();
+
+    assert_eq!(aland.country_code, 358);
+    assert_eq!(aland.area_codes, &["18"]);
+    assert_eq!(Country::AG.calling_code().map(|c|c.country_code), Some(1));
+}
+
+#[test]
+fn countries_are_found_by_every_code() {
+    assert_eq!(Country::from_alpha3("ALA"), Some(Country::AX));
+    assert_eq!(Country::from_numeric(752), Some(Country::SE));
+    assert_eq!(Country::from_alpha2("fi"), None);
+
+    for c in Country::ALL {
+        assert_eq!(Country::from_alpha2(c.alpha2()), Some(c));
+    }
+}
+
+#[test]
+fn currencies_and_languages_are_shared() {
+    assert_eq!(Country::FI.currencies(), &[currency::EUR]);
+    assert_eq!(Country::AX.languages(), &[language::SWE]);
+    // Finnish is spoken in Sweden without being official there
+    assert_eq!(Country::SE.languages(), &[language::FIN, language::SWE]);
+    assert_eq!(Country::SE.official_languages(), &[language::SWE]);
+    assert_eq!(currency::USD.fraction_basic, 100);
+    assert!(Country::US.currencies().contains(&currency::USD) && Country::PR.currencies().contains(&currency::USD));
+}
@@ -0,0 +1,141 @@
+// Each test crate uses its own subset of these helpers
+#![allow(dead_code)]
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::env::var;
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use scraper::Html;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+use wikibase::fetch::{Fetcher, Mode};
+use wikibase::snapshot::Snapshots;
+use wikibase::Identifier;
+
+
+pub fn fixtures() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+/// Hand-written pages modeled after the source pages, laid out like recorded snapshots.
+/// Tables are trimmed to a few rows and images point to placeholders, they don't follow changes of the real layouts.
+pub fn pages() -> PathBuf {
+    fixtures().join("synthetic_pages")
+}
+
+/// Synthetic copy of the page behind `url`, read through the same snapshot store the scraper replays from
+pub async fn page(url: &str) -> Html {
+    let fetcher = Fetcher::new(Mode::Replay(Snapshots::new(pages())));
+
+    fetcher.get_html(url).await.unwrap_or_else(|e|panic!("{}", e))
+}
+
+/// The same input countries the scraper uses
+pub fn countries() -> BTreeMap<Identifier, Vec<String>> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("input").join("countries.json");
+    let data = read_to_string(path).unwrap();
+
+    serde_json::from_str(&data).unwrap()
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    fixtures().join("expected").join(format!("{}.json", name))
+}
+
+/// Expected output of another parser, used as input for the parsers that depend on it
+pub fn expected<T>(name: &str) -> T where T: DeserializeOwned {
+    let path = golden_path(name);
+    let data = read_to_string(&path).unwrap_or_else(|e|panic!("Failed to read {}: {}", path.to_string_lossy(), e));
+
+    serde_json::from_str(&data).unwrap_or_else(|e|panic!("Failed to parse {}: {}", path.to_string_lossy(), e))
+}
+
+/// Compare parsed data with the golden file, set `UPDATE_GOLDEN=1` to rewrite the file instead
+pub fn assert_golden<T>(name: &str, parsed: &T) where T: Serialize {
+    let path = golden_path(name);
+    let parsed = serde_json::to_value(parsed).unwrap();
+
+    if var("UPDATE_GOLDEN").is_ok_and(|v|v == "1") {
+        let json = serde_json::to_string_pretty(&parsed).unwrap();
+        write(&path, json + "\n").unwrap();
+
+        return
+    }
+
+    let expected = expected::<Value>(name);
+    let mut lines = Vec::new();
+
+    diff("", &expected, &parsed, &mut lines);
+
+    if !lines.is_empty() {
+        panic!(
+            "Parsed {} does not match {} ({} differences, rerun with UPDATE_GOLDEN=1 to accept):\n{}",
+            name,
+            path.to_string_lossy(),
+            lines.len(),
+            lines.join("\n")
+        );
+    }
+}
+
+fn diff(path: &str, expected: &Value, parsed: &Value, lines: &mut Vec<String>) {
+    match (expected, parsed) {
+        (Value::Object(e), Value::Object(p)) => {
+            let keys = e.keys().chain(p.keys()).collect::<BTreeSet<&String>>();
+
+            for k in keys {
+                diff_child(&child(path, k), e.get(k), p.get(k), lines);
+            }
+        },
+        (Value::Array(e), Value::Array(p)) => {
+            for i in 0..e.len().max(p.len()) {
+                diff_child(&child(path, &i.to_string()), e.get(i), p.get(i), lines);
+            }
+        },
+        (e, p) => if e != p {
+            lines.push(format!("  ~ {}: expected {}, parsed {}", path, e, p));
+        },
+    }
+}
+
+fn diff_child(path: &str, expected: Option<&Value>, parsed: Option<&Value>, lines: &mut Vec<String>) {
+    match (expected, parsed) {
+        (Some(e), Some(p)) => diff(path, e, p, lines),
+        (Some(e), None) => lines.push(format!("  - {}: {} (missing from parsed)", path, e)),
+        (None, Some(p)) => lines.push(format!("  + {}: {} (not expected)", path, p)),
+        (None, None) => (),
+    }
+}
+
+fn child(path: &str, key: &str) -> String {
+    match path.is_empty() {
+        true => key.to_owned(),
+        false => format!("{}.{}", path, key),
+    }
+}
+
+/// Compare generated source with the file under `tests/fixtures/generated`, set `UPDATE_GOLDEN=1` to rewrite the file instead
+pub fn assert_generated(name: &str, generated: &str) {
+    let path = fixtures().join("generated").join(name);
+
+    if var("UPDATE_GOLDEN").is_ok_and(|v|v == "1") {
+        write(&path, generated).unwrap();
+        return
+    }
+
+    let expected = read_to_string(&path).unwrap_or_else(|e|panic!("Failed to read {}: {}", path.to_string_lossy(), e));
+
+    if let Some((i, (e, g))) = expected.lines().zip(generated.lines()).enumerate().find(|(_, (e, g))|e != g) {
+        panic!(
+            "Generated {} differs from {} on line {} (rerun with UPDATE_GOLDEN=1 to accept):\n  - {}\n  + {}",
+            name, path.to_string_lossy(), i + 1, e, g
+        );
+    }
+
+    assert_eq!(
+        expected.lines().count(), generated.lines().count(),
+        "Generated {} has a different number of lines than {}", name, path.to_string_lossy()
+    );
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::env::temp_dir;
+use std::fs::{create_dir_all, read_to_string, remove_dir_all, write};
+use std::path::PathBuf;
+
+use common::expected;
+use wikibase::types::{Country, CountryLanguage, Flag, LanguageStatus};
+use wikibase::{Dataset, Identifier};
+
+
+fn dataset() -> Dataset {
+    let finland = Identifier::new("fi");
+
+    Dataset {
+        sovereign_states: expected("sovereign_states"),
+        regions: expected("regions"),
+        flags: BTreeMap::from([(finland.clone(), Flag::new(finland, PathBuf::from("output/flags/fi")))]),
+        emojis: expected("emojis"),
+        currencies: expected("currencies"),
+        calling_codes: expected("calling_codes"),
+        languages: expected("language_zones"),
+        capitals: expected("capitals"),
+        ..Dataset::default()
+    }
+}
+
+#[test]
+fn every_region_is_a_country() {
+    let data = dataset();
+    let countries = Country::from_dataset(&data);
+
+    assert_eq!(countries.keys().collect::<Vec<_>>(), data.regions.keys().collect::<Vec<_>>());
+}
+
+#[test]
+fn country_joins_other_data_sets() {
+    let countries = Country::from_dataset(&dataset());
+    let finland = &countries[&Identifier::new("fi")];
+
+    assert_eq!(finland.region.iso_3166_1.a2, "FI");
+    assert_eq!(finland.sovereign_state.as_ref().map(|s|s.name_short.as_str()), Some("Finland"));
+    assert_eq!(finland.capital.as_ref().map(|c|c.name.as_str()), Some("Helsinki"));
+    assert_eq!(finland.emoji.as_deref(), Some("🇫🇮"));
+    assert_eq!(finland.flag.first(), Some(&PathBuf::from("output/flags/fi/source.png")));
+    // Round variations are only made with the flags feature
+    assert_eq!(finland.flag.len(), if cfg!(feature = "flags") { 8 } else { 1 });
+    assert!(finland.currencies.contains_key(&Identifier::new("eur")));
+    assert!(finland.languages.contains_key(&Identifier::new("fin")));
+
+    // Åland belongs to Finland but has data of its own
+    let aland = &countries[&Identifier::new("ax")];
+
+    assert_eq!(aland.sovereign_state.as_ref().map(|s|s.name_short.as_str()), Some("Finland"));
+    assert_eq!(aland.capital.as_ref().map(|c|c.name.as_str()), Some("Mariehamn"));
+    assert!(aland.flag.is_empty());
+}
+
+#[test]
+fn country_is_written_as_one_object() {
+    let countries = Country::from_dataset(&dataset());
+    let json = serde_json::to_value(&countries[&Identifier::new("fi")]).unwrap();
+
+    // Region fields are on the top level next to the joined data
+    assert_eq!(json["name"], "Finland");
+    assert_eq!(json["iso_3166_1"]["a3"], "FIN");
+    assert_eq!(json["calling_code"]["country_code"], 358);
+}
+
+#[test]
+fn languages_are_viewed_from_the_country() {
+    let countries = Country::from_dataset(&dataset());
+    let finland = &countries[&Identifier::new("fi")];
+    let swedish = &finland.languages[&Identifier::new("swe")];
+
+    assert_eq!(swedish.name, "Swedish");
+    assert_eq!(swedish.status, Some(LanguageStatus::CoOfficial));
+
+    // Regions the language is spoken in elsewhere stay in the languages data set
+    let json = serde_json::to_value(swedish).unwrap();
+
+    assert!(json.get("regions").is_none());
+}
+
+#[test]
+fn countries_embedding_whole_languages_are_read() {
+    let json = r#"{"name_short": "Swedish", "name_long": "Swedish", "iso639": {"set1": "sv", "set2_t": "swe", "set2_b": "swe", "set3": "swe"}, "regions": ["ax", "fi", "se"]}"#;
+    let swedish = serde_json::from_str::<CountryLanguage>(json).unwrap();
+
+    assert_eq!(swedish.name, "Swedish");
+    assert_eq!(swedish.status, None);
+    assert!(swedish.is_official());
+}
+
+#[test]
+fn currencies_are_viewed_from_the_country() {
+    let countries = Country::from_dataset(&dataset());
+    let euro = &countries[&Identifier::new("fi")].currencies[&Identifier::new("eur")];
+
+    assert_eq!(euro.name, "Euro");
+
+    // Other regions using the euro stay in the currencies data set
+    let json = serde_json::to_value(euro).unwrap();
+
+    assert!(json.get("regions").is_none());
+}
+
+#[tokio::test]
+async fn countries_no_longer_there_are_removed() {
+    let dir = temp_dir().join(format!("wikibase-countries-{}", std::process::id()));
+    let _ = remove_dir_all(&dir);
+
+    create_dir_all(&dir).unwrap();
+    write(dir.join("zz.json"), "{}").unwrap();
+
+    Country::write_each(&Country::from_dataset(&dataset()), &dir).await.unwrap();
+
+    assert!(!dir.join("zz.json").exists());
+    assert!(read_to_string(dir.join("fi.json")).unwrap().ends_with("}\n"));
+
+    let _ = remove_dir_all(&dir);
+}
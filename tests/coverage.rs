@@ -0,0 +1,56 @@
+mod common;
+
+use std::collections::BTreeSet;
+
+use common::expected;
+use wikibase::coverage::{Coverage, Requirement};
+use wikibase::{Dataset, Identifier};
+
+
+// Every data set of the fixtures but flags, those are downloaded as images
+fn dataset() -> Dataset {
+    Dataset {
+        regions: expected("regions"),
+        emojis: expected("emojis"),
+        currencies: expected("currencies"),
+        calling_codes: expected("calling_codes"),
+        languages: expected("language_zones"),
+        capitals: expected("capitals"),
+        ..Dataset::default()
+    }
+}
+
+#[test]
+fn un_members_without_flags_have_gaps() {
+    let coverage = Coverage::check(&dataset());
+
+    assert_eq!(coverage.checked, 6);
+    assert!(!coverage.is_complete());
+
+    for (id, missing) in &coverage.gaps {
+        assert_eq!(missing, &BTreeSet::from([Requirement::Flag]), "Unexpected gaps for {}", id);
+    }
+
+    // Åland is not a UN member, it doesn't have to have everything
+    assert!(!coverage.gaps.contains_key(&Identifier::new("ax")));
+}
+
+#[test]
+fn missing_data_is_listed_per_region() {
+    let mut data = dataset();
+    let sweden = Identifier::new("se");
+
+    data.capitals.remove(&sweden);
+    data.currencies.values_mut().for_each(|c|c.regions.retain(|r|r != &sweden));
+
+    let coverage = Coverage::check(&data);
+
+    assert_eq!(
+        coverage.gaps[&sweden],
+        BTreeSet::from([Requirement::Flag, Requirement::Currency, Requirement::Capital])
+    );
+
+    let matrix = coverage.to_string();
+    assert!(matrix.lines().any(|l|l.starts_with("se ") && l.matches("missing").count() == 3), "{}", matrix);
+    assert!(matrix.ends_with("6 of 6 UN members have gaps\n"), "{}", matrix);
+}
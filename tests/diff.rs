@@ -0,0 +1,80 @@
+mod common;
+
+use std::env::temp_dir;
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+use common::expected;
+use serde_json::{json, Value};
+use wikibase::diff::Diff;
+use wikibase::DatasetKind;
+
+
+#[test]
+fn changes_are_listed_by_path() {
+    let old = expected::<Value>("capitals");
+    let mut new = old.clone();
+
+    new["fi"]["name"] = json!("Helsingfors");
+    new["se"]["endonyms"] = json!(["Stockholm"]);
+    new.as_object_mut().unwrap().remove("us");
+
+    let mut diff = Diff::default();
+    diff.compare(DatasetKind::Capitals, Some(&old), Some(&new));
+
+    assert_eq!(diff.to_string(), [
+        "capitals",
+        "  ~ fi.name: \"Helsinki\" -> \"Helsingfors\"",
+        "  + se.endonyms: [\"Stockholm\"]",
+        "  - us: Washington, D.C.",
+        "",
+    ].join("\n"));
+
+    assert_eq!(diff.patch(), json!({
+        "capitals": [
+            { "op": "replace", "path": "/fi/name", "value": "Helsingfors" },
+            { "op": "add", "path": "/se/endonyms", "value": ["Stockholm"] },
+            { "op": "remove", "path": "/us" },
+        ]
+    }));
+}
+
+#[test]
+fn sets_list_the_items_that_differ() {
+    let old = expected::<Value>("currencies");
+    let mut new = old.clone();
+
+    new["usd"]["regions"] = json!(["ec", "pr"]);
+
+    let mut diff = Diff::default();
+    diff.compare(DatasetKind::Currencies, Some(&old), Some(&new));
+
+    assert_eq!(diff.changes.len(), 1);
+    assert_eq!(diff.changes[0].pointer(), "/usd/regions");
+    assert_eq!(diff.changes[0].to_string(), "~ usd.regions: +ec -us");
+}
+
+#[test]
+fn output_directories_are_compared() {
+    let dir = temp_dir().join(format!("wikibase-diff-{}", std::process::id()));
+    let (previous, current) = (dir.join("previous"), dir.join("current"));
+
+    for (d, basic) in [(&previous, 100), (&current, 1000)] {
+        create_dir_all(d).unwrap();
+        write(d.join("currencies.json"), json!({ "xyz": { "name": "Xyz", "symbol": "X", "fraction": { "name": "Y", "basic": basic }, "regions": [] } }).to_string()).unwrap();
+    }
+
+    write(current.join("emojis.json"), json!({ "fi": "🇫🇮" }).to_string()).unwrap();
+
+    let diff = Diff::read(&previous, &current, DatasetKind::ALL).unwrap();
+    assert_eq!(diff.to_string(), "emojis\n  + data set added\ncurrencies\n  ~ xyz.fraction.basic: 100 -> 1000\n");
+
+    // Root of a data set can't be removed, only replaced
+    let diff = Diff::read(&current, &previous, [DatasetKind::Emojis]).unwrap();
+    assert_eq!(diff.to_string(), "emojis\n  - data set removed\n");
+    assert_eq!(diff.patch(), json!({ "emojis": [{ "op": "replace", "path": "", "value": null }] }));
+
+    // Nothing changes between a directory and itself
+    assert!(Diff::read(&current, &current, DatasetKind::ALL).unwrap().is_empty());
+
+    let _ = remove_dir_all(&dir);
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use std::env::temp_dir;
+use std::fs::{read_to_string, remove_dir_all, remove_file};
+use std::path::PathBuf;
+
+use common::{expected, fixtures};
+use rusqlite::Connection;
+use wikibase::export::csv::{self, CsvOptions};
+use wikibase::export::sqlite;
+use wikibase::types::Language;
+use wikibase::{Dataset, DatasetKind, ErrorKind, Identifier};
+
+
+fn dataset() -> Dataset {
+    Dataset {
+        sovereign_states: expected("sovereign_states"),
+        regions: expected("regions"),
+        emojis: expected("emojis"),
+        currencies: expected("currencies"),
+        calling_codes: expected("calling_codes"),
+        languages: expected("language_zones"),
+        capitals: expected("capitals"),
+        ..Dataset::default()
+    }
+}
+
+fn database(name: &str) -> PathBuf {
+    temp_dir().join(format!("wikibase-{}-{}.sqlite", name, std::process::id()))
+}
+
+#[test]
+fn sqlite_tables_can_be_joined() {
+    let path = database("joined");
+    sqlite::write(&dataset(), &path).unwrap();
+
+    let conn = Connection::open(&path).unwrap();
+    let (capital, code, areas, emoji): (String, u16, String, String) = conn.query_row(
+        "SELECT c.name, cc.country_code, cc.area_codes, r.emoji FROM regions r
+        JOIN capitals c ON c.region = r.id
+        JOIN calling_codes cc ON cc.region = r.id
+        WHERE r.a2 = 'AX'",
+        [],
+        |r|Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?))
+    ).unwrap();
+
+    assert_eq!((capital.as_str(), code, areas.as_str(), emoji.as_str()), ("Mariehamn", 358, "18", "🇦🇽"));
+
+    let swedish = conn.prepare("SELECT region FROM language_regions WHERE language = 'swe' ORDER BY region").unwrap()
+        .query_map([], |r|r.get::<_, String>(0)).unwrap()
+        .collect::<Result<Vec<String>, _>>().unwrap();
+
+    assert_eq!(swedish, ["ax", "fi", "se"]);
+
+    let _ = remove_file(&path);
+}
+
+#[test]
+fn sqlite_export_keeps_foreign_keys() {
+    let path = database("foreign");
+    let mut data = dataset();
+
+    // Capital of a region we know nothing of
+    let capital = data.capitals.values().next().cloned().unwrap();
+    data.capitals.insert(Identifier::new("zz"), capital);
+
+    let err = sqlite::write(&data, &path).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Export);
+    assert!(err.to_string().contains("FOREIGN KEY"), "Unexpected error: {}", err);
+
+    let _ = remove_file(&path);
+}
+
+#[test]
+fn sqlite_members_refer_to_macrolanguages() {
+    let path = database("macrolanguages");
+    let mut data = dataset();
+
+    let sil = fixtures().join("sil");
+    let table = |name|read_to_string(sil.join(name)).unwrap();
+    Language::merge_sil(&table("iso-639-3.tab"), &table("iso-639-3-macrolanguages.tab"), &mut data.languages).unwrap();
+
+    // Members are inserted before their macrolanguage
+    sqlite::write(&data, &path).unwrap();
+
+    let conn = Connection::open(&path).unwrap();
+    let chinese = conn.prepare("SELECT m.code, l.name_short FROM languages m JOIN languages l ON m.macrolanguage = l.code WHERE l.type = 'living' ORDER BY m.code").unwrap()
+        .query_map([], |r|Ok((r.get::<_, String>(0)?, r.get::<_, String>(1)?))).unwrap()
+        .collect::<Result<Vec<(String, String)>, _>>().unwrap();
+
+    assert_eq!(chinese, [("cmn".to_string(), "Chinese".to_string()), ("yue".to_string(), "Chinese".to_string())]);
+
+    let _ = remove_file(&path);
+}
+
+#[test]
+fn csv_flattens_nested_values() {
+    let dir = temp_dir().join(format!("wikibase-csv-{}", std::process::id()));
+    let options = CsvOptions { separator: "|".to_string(), ..CsvOptions::default() };
+
+    csv::write(&dataset(), &dir, &options).unwrap();
+
+    let regions = read_to_string(csv::file_path(&dir, DatasetKind::Regions, &options)).unwrap();
+    assert!(regions.starts_with("id,name,state_name,un_member,sovereignity,a2,a3,num,iso_3166_2,tld\n"), "{}", regions);
+    assert!(regions.contains("\nax,Åland,Åland,false,fi,AX,ALA,248,ISO 3166-2:AX,.ax\n"), "{}", regions);
+
+    // US dollar is in use in Puerto Rico and the United States, a row for each
+    let currencies = read_to_string(csv::file_path(&dir, DatasetKind::Currencies, &options)).unwrap();
+    let dollars = currencies.lines().filter(|l|l.starts_with("usd,")).collect::<Vec<&str>>();
+
+    assert_eq!(dollars.len(), 2);
+    assert!(dollars[0].ends_with(",pr") && dollars[1].ends_with(",us"), "{:?}", dollars);
+
+    let languages = read_to_string(csv::file_path(&dir, DatasetKind::Languages, &options)).unwrap();
+    assert!(languages.contains("\nswe,Swedish,Swedish language,sv,swe,swe,swe,,,,ax|fi|se\n"), "{}", languages);
+
+    let _ = remove_dir_all(&dir);
+}
+
+#[test]
+fn tsv_separates_columns_with_tabs() {
+    let dir = temp_dir().join(format!("wikibase-tsv-{}", std::process::id()));
+    let options = CsvOptions::tsv();
+
+    csv::write(&dataset(), &dir, &options).unwrap();
+
+    let path = csv::file_path(&dir, DatasetKind::Capitals, &options);
+    assert!(path.to_string_lossy().ends_with("capitals.tsv"));
+
+    let capitals = read_to_string(path).unwrap();
+    assert!(capitals.contains("\nfi\tHelsinki\tHelsingfors\n"), "{}", capitals);
+
+    let _ = remove_dir_all(&dir);
+}
@@ -0,0 +1,271 @@
+// Generated by wikibase from data collected from Wikipedia, do not edit
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Currency {
+    /// ISO 4217 code
+    pub code: &'static str,
+    pub name: &'static str,
+    pub symbol: &'static str,
+    /// Name of the fractional unit
+    pub fraction: &'static str,
+    /// Number of fractional units in one
+    pub fraction_basic: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    pub name: &'static str,
+    pub iso_639_1: &'static str,
+    pub iso_639_2_t: &'static str,
+    pub iso_639_2_b: &'static str,
+    pub iso_639_3: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallingCode {
+    pub country_code: u16,
+    /// National prefixes following the country code, empty when the country code alone tells the country
+    pub area_codes: &'static [&'static str],
+}
+
+/// Currencies by their ISO 4217 code
+pub mod currency {
+    use super::Currency;
+
+    pub const BTN: Currency = Currency { code: "BTN", name: "Bhutanese ngultrum", symbol: "Nu.", fraction: "Chhertum", fraction_basic: 100 };
+    pub const CHF: Currency = Currency { code: "CHF", name: "Swiss franc", symbol: "Fr.", fraction: "Rappen", fraction_basic: 100 };
+    pub const EUR: Currency = Currency { code: "EUR", name: "Euro", symbol: "€", fraction: "Cent", fraction_basic: 100 };
+    pub const INR: Currency = Currency { code: "INR", name: "Indian rupee", symbol: "₹", fraction: "Paisa", fraction_basic: 100 };
+    pub const SEK: Currency = Currency { code: "SEK", name: "Swedish krona", symbol: "kr", fraction: "Öre", fraction_basic: 100 };
+    pub const USD: Currency = Currency { code: "USD", name: "United States dollar", symbol: "$", fraction: "Cent", fraction_basic: 100 };
+    pub const XCD: Currency = Currency { code: "XCD", name: "Eastern Caribbean dollar", symbol: "$", fraction: "Cent", fraction_basic: 100 };
+}
+
+/// Languages by their ISO 639-3 code
+pub mod language {
+    use super::Language;
+
+    pub const DEU: Language = Language { name: "German", iso_639_1: "de", iso_639_2_t: "deu", iso_639_2_b: "ger", iso_639_3: "deu" };
+    pub const DZO: Language = Language { name: "Dzongkha", iso_639_1: "dz", iso_639_2_t: "dzo", iso_639_2_b: "dzo", iso_639_3: "dzo" };
+    pub const ENG: Language = Language { name: "English", iso_639_1: "en", iso_639_2_t: "eng", iso_639_2_b: "eng", iso_639_3: "eng" };
+    pub const FIN: Language = Language { name: "Finnish", iso_639_1: "fi", iso_639_2_t: "fin", iso_639_2_b: "fin", iso_639_3: "fin" };
+    pub const FRA: Language = Language { name: "French", iso_639_1: "fr", iso_639_2_t: "fra", iso_639_2_b: "fre", iso_639_3: "fra" };
+    pub const ITA: Language = Language { name: "Italian", iso_639_1: "it", iso_639_2_t: "ita", iso_639_2_b: "ita", iso_639_3: "ita" };
+    pub const ROH: Language = Language { name: "Romansh", iso_639_1: "rm", iso_639_2_t: "roh", iso_639_2_b: "roh", iso_639_3: "roh" };
+    pub const SME: Language = Language { name: "Northern Sami", iso_639_1: "se", iso_639_2_t: "sme", iso_639_2_b: "sme", iso_639_3: "sme" };
+    pub const SWE: Language = Language { name: "Swedish", iso_639_1: "sv", iso_639_2_t: "swe", iso_639_2_b: "swe", iso_639_3: "swe" };
+    pub const ZHO: Language = Language { name: "Chinese", iso_639_1: "zh", iso_639_2_t: "zho", iso_639_2_b: "chi", iso_639_3: "zho" };
+}
+
+pub const CURRENCIES: &[Currency] = &[currency::BTN, currency::CHF, currency::EUR, currency::INR, currency::SEK, currency::USD, currency::XCD];
+pub const LANGUAGES: &[Language] = &[language::DEU, language::DZO, language::ENG, language::FIN, language::FRA, language::ITA, language::ROH, language::SME, language::SWE, language::ZHO];
+
+/// Countries by their ISO 3166-1 alpha-2 code
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Country {
+    /// Antigua and Barbuda
+    AG,
+    /// Åland
+    AX,
+    /// Bhutan
+    BT,
+    /// Switzerland
+    CH,
+    /// Finland
+    FI,
+    /// Puerto Rico
+    PR,
+    /// Sweden
+    SE,
+    /// United States
+    US,
+}
+
+impl Country {
+    pub const ALL: [Self; 8] = [
+        Self::AG,
+        Self::AX,
+        Self::BT,
+        Self::CH,
+        Self::FI,
+        Self::PR,
+        Self::SE,
+        Self::US,
+    ];
+
+    /// Country of an upper case ISO 3166-1 alpha-2 code
+    pub const fn from_alpha2(code: &str) -> Option<Self> {
+        match code.as_bytes() {
+            b"AG" => Some(Self::AG),
+            b"AX" => Some(Self::AX),
+            b"BT" => Some(Self::BT),
+            b"CH" => Some(Self::CH),
+            b"FI" => Some(Self::FI),
+            b"PR" => Some(Self::PR),
+            b"SE" => Some(Self::SE),
+            b"US" => Some(Self::US),
+            _ => None,
+        }
+    }
+    /// Country of an upper case ISO 3166-1 alpha-3 code
+    pub const fn from_alpha3(code: &str) -> Option<Self> {
+        match code.as_bytes() {
+            b"ATG" => Some(Self::AG),
+            b"ALA" => Some(Self::AX),
+            b"BTN" => Some(Self::BT),
+            b"CHE" => Some(Self::CH),
+            b"FIN" => Some(Self::FI),
+            b"PRI" => Some(Self::PR),
+            b"SWE" => Some(Self::SE),
+            b"USA" => Some(Self::US),
+            _ => None,
+        }
+    }
+    /// Country of an ISO 3166-1 numeric code
+    pub const fn from_numeric(code: u16) -> Option<Self> {
+        match code {
+            28 => Some(Self::AG),
+            248 => Some(Self::AX),
+            64 => Some(Self::BT),
+            756 => Some(Self::CH),
+            246 => Some(Self::FI),
+            630 => Some(Self::PR),
+            752 => Some(Self::SE),
+            840 => Some(Self::US),
+            _ => None,
+        }
+    }
+    pub const fn alpha2(self) -> &'static str {
+        match self {
+            Self::AG => "AG",
+            Self::AX => "AX",
+            Self::BT => "BT",
+            Self::CH => "CH",
+            Self::FI => "FI",
+            Self::PR => "PR",
+            Self::SE => "SE",
+            Self::US => "US",
+        }
+    }
+    pub const fn alpha3(self) -> &'static str {
+        match self {
+            Self::AG => "ATG",
+            Self::AX => "ALA",
+            Self::BT => "BTN",
+            Self::CH => "CHE",
+            Self::FI => "FIN",
+            Self::PR => "PRI",
+            Self::SE => "SWE",
+            Self::US => "USA",
+        }
+    }
+    pub const fn numeric(self) -> u16 {
+        match self {
+            Self::AG => 28,
+            Self::AX => 248,
+            Self::BT => 64,
+            Self::CH => 756,
+            Self::FI => 246,
+            Self::PR => 630,
+            Self::SE => 752,
+            Self::US => 840,
+        }
+    }
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::AG => "Antigua and Barbuda",
+            Self::AX => "Åland",
+            Self::BT => "Bhutan",
+            Self::CH => "Switzerland",
+            Self::FI => "Finland",
+            Self::PR => "Puerto Rico",
+            Self::SE => "Sweden",
+            Self::US => "United States",
+        }
+    }
+    pub const fn un_member(self) -> bool {
+        match self {
+            Self::AG => true,
+            Self::AX => false,
+            Self::BT => true,
+            Self::CH => true,
+            Self::FI => true,
+            Self::PR => false,
+            Self::SE => true,
+            Self::US => true,
+        }
+    }
+    pub const fn calling_code(self) -> Option<CallingCode> {
+        match self {
+            Self::AG => Some(CallingCode { country_code: 1, area_codes: &["268"] }),
+            Self::AX => Some(CallingCode { country_code: 358, area_codes: &["18"] }),
+            Self::BT => Some(CallingCode { country_code: 975, area_codes: &[] }),
+            Self::CH => Some(CallingCode { country_code: 41, area_codes: &[] }),
+            Self::FI => Some(CallingCode { country_code: 358, area_codes: &[] }),
+            Self::PR => Some(CallingCode { country_code: 1, area_codes: &["787"] }),
+            Self::SE => Some(CallingCode { country_code: 46, area_codes: &[] }),
+            Self::US => Some(CallingCode { country_code: 1, area_codes: &[] }),
+        }
+    }
+    pub const fn emoji(self) -> Option<&'static str> {
+        match self {
+            Self::AG => Some("🇦🇬"),
+            Self::AX => Some("🇦🇽"),
+            Self::BT => Some("🇧🇹"),
+            Self::CH => Some("🇨🇭"),
+            Self::FI => Some("🇫🇮"),
+            Self::PR => Some("🇵🇷"),
+            Self::SE => Some("🇸🇪"),
+            Self::US => Some("🇺🇸"),
+        }
+    }
+    pub const fn capital(self) -> Option<&'static str> {
+        match self {
+            Self::AG => Some("St. John's"),
+            Self::AX => Some("Mariehamn"),
+            Self::BT => Some("Thimphu"),
+            Self::CH => Some("Bern"),
+            Self::FI => Some("Helsinki"),
+            Self::PR => Some("San Juan"),
+            Self::SE => Some("Stockholm"),
+            Self::US => Some("Washington, D.C."),
+        }
+    }
+    pub const fn currencies(self) -> &'static [Currency] {
+        match self {
+            Self::AG => &[currency::XCD],
+            Self::AX => &[],
+            Self::BT => &[currency::BTN, currency::INR],
+            Self::CH => &[currency::CHF],
+            Self::FI => &[currency::EUR],
+            Self::PR => &[currency::USD],
+            Self::SE => &[currency::SEK],
+            Self::US => &[currency::USD],
+        }
+    }
+    pub const fn languages(self) -> &'static [Language] {
+        match self {
+            Self::AG => &[language::ENG],
+            Self::AX => &[language::SWE],
+            Self::BT => &[language::DZO],
+            Self::CH => &[language::DEU, language::FRA, language::ITA, language::ROH],
+            Self::FI => &[language::FIN, language::SWE],
+            Self::PR => &[language::ENG],
+            Self::SE => &[language::FIN, language::SWE],
+            Self::US => &[language::ENG],
+        }
+    }
+    pub const fn official_languages(self) -> &'static [Language] {
+        match self {
+            Self::AG => &[language::ENG],
+            Self::AX => &[language::SWE],
+            Self::BT => &[language::DZO],
+            Self::CH => &[language::DEU, language::FRA, language::ITA, language::ROH],
+            Self::FI => &[language::FIN, language::SWE],
+            Self::PR => &[language::ENG],
+            Self::SE => &[language::SWE],
+            Self::US => &[language::ENG],
+        }
+    }
+}
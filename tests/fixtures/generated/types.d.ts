@@ -0,0 +1,163 @@
+// Generated by wikibase from the JSON Schemas of the data sets, do not edit
+
+/** ITU country calling code, with the area codes that tell regions sharing the country code apart */
+export interface CallingCode {
+    country_code: number;
+    /** World numbering zone, the first digit of the country code */
+    zone: number;
+    /** National prefixes following the country code, such as 268 of Antigua and Barbuda in +1 268 */
+    area_codes?: string[];
+}
+
+export interface Capital {
+    name: string;
+    endonyms?: string[] | null;
+}
+
+/** Region joined with everything the other data sets know of it */
+export interface Country {
+    name: string;
+    state_name: string;
+    un_member: boolean;
+    sovereignity: Identifier;
+    iso_3166_1: Iso3166_1;
+    iso_3166_2: Iso3166_2;
+    tld: Tld;
+    calling_code?: CallingCode | null;
+    capital?: Capital | null;
+    /** Currencies in use by their ISO 4217 code */
+    currencies?: Record<string, CountryCurrency>;
+    emoji?: string | null;
+    /** Source image and transformations of the flag */
+    flag?: string[];
+    /** Languages spoken by their ISO 639-3 code */
+    languages?: Record<string, CountryLanguage>;
+    /** Sovereign state the region belongs to, the region itself if it's a sovereign state */
+    sovereign_state?: SovereignState | null;
+}
+
+/** Currency as used in one country, the regions of the currency are left to the currencies data set */
+export interface CountryCurrency {
+    name: string;
+    symbol: string;
+    fraction: Fraction;
+}
+
+/** Language as spoken in one country, the regions of the language are left to the languages data set */
+export interface CountryLanguage {
+    /** Short name of the language */
+    name: string;
+    iso639: Iso639;
+    /** Status of the language in this country, unknown for languages read from data sets written before statuses were recorded */
+    status?: LanguageStatus | null;
+}
+
+export interface Currency {
+    name: string;
+    symbol: string;
+    fraction: Fraction;
+    regions: Identifier[];
+}
+
+export interface Flag {
+    sovereignity: Identifier;
+    dir: string;
+}
+
+export interface Fraction {
+    name: string;
+    basic: number;
+}
+
+export type Identifier = string;
+
+export interface Iso3166_1 {
+    a2: string;
+    a3: string;
+    num: number;
+}
+
+export type Iso3166_2 = string;
+
+/** Codes of a language, only ISO 639-3 has a code for every language */
+export interface Iso639 {
+    set1: string;
+    set2_t: string;
+    set2_b: string;
+    set3: string;
+}
+
+export interface Language {
+    name_short: string;
+    name_long: string;
+    iso639: Iso639;
+    /** ISO 639-3 code of the macrolanguage this language belongs to, `zho` for `cmn` */
+    macrolanguage?: Identifier | null;
+    /** Regions the language is spoken in, in the order of their identifiers */
+    regions?: LanguageRegion[];
+    /** Only known for languages read from the SIL code tables */
+    scope?: LanguageScope | null;
+    /** Only known for languages read from the SIL code tables */
+    type?: LanguageType | null;
+}
+
+/** Region a language is spoken in, and the status of the language there */
+export interface LanguageRegion {
+    region: Identifier;
+    /** Unknown for languages read from data sets written before statuses were recorded */
+    status?: LanguageStatus | null;
+}
+
+/** What an ISO 639-3 code stands for */
+export type LanguageScope = "individual" | "macrolanguage" | "special";
+
+/** Standing of a language in a region, from the strongest to the weakest */
+export type LanguageStatus = "official" | "co_official" | "national" | "regional" | "minority";
+
+/** Whether a language is spoken today, was once or was made up */
+export type LanguageType = "living" | "extinct" | "ancient" | "historical" | "constructed" | "special";
+
+export interface Region {
+    name: string;
+    state_name: string;
+    un_member: boolean;
+    sovereignity: Identifier;
+    iso_3166_1: Iso3166_1;
+    iso_3166_2: Iso3166_2;
+    tld: Tld;
+}
+
+export interface SovereignState {
+    name_short: string;
+    name_long: string;
+    un_member: boolean;
+    disputed: boolean;
+}
+
+export type Tld = string[];
+
+export interface UNMember {
+    name: string;
+    iso_3166?: Identifier | null;
+}
+
+/** Contents of un_nations.json */
+export type UnNations = UNMember[];
+/** Contents of sovereign_states.json */
+export type SovereignStates = Record<string, SovereignState>;
+/** Contents of regions.json */
+export type Regions = Record<string, Region>;
+/** Contents of flags.json */
+export type Flags = Record<string, Flag>;
+/** Contents of emojis.json */
+export type Emojis = Record<string, string>;
+/** Contents of currencies.json */
+export type Currencies = Record<string, Currency>;
+/** Contents of calling_codes.json */
+export type CallingCodes = Record<string, CallingCode>;
+/** Contents of languages.json */
+export type Languages = Record<string, Language>;
+/** Contents of capitals.json */
+export type Capitals = Record<string, Capital>;
+/** Contents of countries.json */
+export type Countries = Record<string, Country>;
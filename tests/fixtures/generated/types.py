@@ -0,0 +1,186 @@
+# Generated by wikibase from the JSON Schemas of the data sets, do not edit
+# Requires Python 3.8 or later, and typing_extensions before Python 3.11
+
+from __future__ import annotations
+
+from typing import Any, Dict, List, Literal, Optional
+
+try:
+    from typing import NotRequired, TypeAlias, TypedDict
+except ImportError:
+    from typing_extensions import NotRequired, TypeAlias, TypedDict
+
+
+class CallingCode(TypedDict):
+    """ITU country calling code, with the area codes that tell regions sharing the country code apart"""
+
+    country_code: int
+    # World numbering zone, the first digit of the country code
+    zone: int
+    # National prefixes following the country code, such as 268 of Antigua and Barbuda in +1 268
+    area_codes: NotRequired[List[str]]
+
+
+class Capital(TypedDict):
+    name: str
+    endonyms: NotRequired[Optional[List[str]]]
+
+
+class Country(TypedDict):
+    """Region joined with everything the other data sets know of it"""
+
+    name: str
+    state_name: str
+    un_member: bool
+    sovereignity: Identifier
+    iso_3166_1: Iso3166_1
+    iso_3166_2: Iso3166_2
+    tld: Tld
+    calling_code: NotRequired[Optional[CallingCode]]
+    capital: NotRequired[Optional[Capital]]
+    # Currencies in use by their ISO 4217 code
+    currencies: NotRequired[Dict[str, CountryCurrency]]
+    emoji: NotRequired[Optional[str]]
+    # Source image and transformations of the flag
+    flag: NotRequired[List[str]]
+    # Languages spoken by their ISO 639-3 code
+    languages: NotRequired[Dict[str, CountryLanguage]]
+    # Sovereign state the region belongs to, the region itself if it's a sovereign state
+    sovereign_state: NotRequired[Optional[SovereignState]]
+
+
+class CountryCurrency(TypedDict):
+    """Currency as used in one country, the regions of the currency are left to the currencies data set"""
+
+    name: str
+    symbol: str
+    fraction: Fraction
+
+
+class CountryLanguage(TypedDict):
+    """Language as spoken in one country, the regions of the language are left to the languages data set"""
+
+    # Short name of the language
+    name: str
+    iso639: Iso639
+    # Status of the language in this country, unknown for languages read from data sets written before statuses were recorded
+    status: NotRequired[Optional[LanguageStatus]]
+
+
+class Currency(TypedDict):
+    name: str
+    symbol: str
+    fraction: Fraction
+    regions: List[Identifier]
+
+
+class Flag(TypedDict):
+    sovereignity: Identifier
+    dir: str
+
+
+class Fraction(TypedDict):
+    name: str
+    basic: int
+
+
+Identifier: TypeAlias = str
+
+
+class Iso3166_1(TypedDict):
+    a2: str
+    a3: str
+    num: int
+
+
+Iso3166_2: TypeAlias = str
+
+
+class Iso639(TypedDict):
+    """Codes of a language, only ISO 639-3 has a code for every language"""
+
+    set1: str
+    set2_t: str
+    set2_b: str
+    set3: str
+
+
+class Language(TypedDict):
+    name_short: str
+    name_long: str
+    iso639: Iso639
+    # ISO 639-3 code of the macrolanguage this language belongs to, `zho` for `cmn`
+    macrolanguage: NotRequired[Optional[Identifier]]
+    # Regions the language is spoken in, in the order of their identifiers
+    regions: NotRequired[List[LanguageRegion]]
+    # Only known for languages read from the SIL code tables
+    scope: NotRequired[Optional[LanguageScope]]
+    # Only known for languages read from the SIL code tables
+    type: NotRequired[Optional[LanguageType]]
+
+
+class LanguageRegion(TypedDict):
+    """Region a language is spoken in, and the status of the language there"""
+
+    region: Identifier
+    # Unknown for languages read from data sets written before statuses were recorded
+    status: NotRequired[Optional[LanguageStatus]]
+
+
+# What an ISO 639-3 code stands for
+LanguageScope: TypeAlias = Literal["individual", "macrolanguage", "special"]
+
+
+# Standing of a language in a region, from the strongest to the weakest
+LanguageStatus: TypeAlias = Literal["official", "co_official", "national", "regional", "minority"]
+
+
+# Whether a language is spoken today, was once or was made up
+LanguageType: TypeAlias = Literal["living", "extinct", "ancient", "historical", "constructed", "special"]
+
+
+class Region(TypedDict):
+    name: str
+    state_name: str
+    un_member: bool
+    sovereignity: Identifier
+    iso_3166_1: Iso3166_1
+    iso_3166_2: Iso3166_2
+    tld: Tld
+
+
+class SovereignState(TypedDict):
+    name_short: str
+    name_long: str
+    un_member: bool
+    disputed: bool
+
+
+Tld: TypeAlias = List[str]
+
+
+class UNMember(TypedDict):
+    name: str
+    iso_3166: NotRequired[Optional[Identifier]]
+
+
+# Contents of un_nations.json
+UnNations: TypeAlias = List[UNMember]
+# Contents of sovereign_states.json
+SovereignStates: TypeAlias = Dict[str, SovereignState]
+# Contents of regions.json
+Regions: TypeAlias = Dict[str, Region]
+# Contents of flags.json
+Flags: TypeAlias = Dict[str, Flag]
+# Contents of emojis.json
+Emojis: TypeAlias = Dict[str, str]
+# Contents of currencies.json
+Currencies: TypeAlias = Dict[str, Currency]
+# Contents of calling_codes.json
+CallingCodes: TypeAlias = Dict[str, CallingCode]
+# Contents of languages.json
+Languages: TypeAlias = Dict[str, Language]
+# Contents of capitals.json
+Capitals: TypeAlias = Dict[str, Capital]
+# Contents of countries.json
+Countries: TypeAlias = Dict[str, Country]
@@ -0,0 +1,140 @@
+use std::env::temp_dir;
+use std::fs::remove_dir_all;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use wikibase::http::{Client, ClientConfig};
+use wikibase::WikibaseError;
+use wiremock::matchers::{header, header_exists, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+
+fn client(config: ClientConfig) -> Client {
+    Client::new(ClientConfig {
+        backoff: Duration::from_millis(1),
+        ..config
+    }).unwrap()
+}
+
+#[tokio::test]
+async fn user_agent_is_sent() {
+    let server = MockServer::start().await;
+    let config = ClientConfig::default();
+
+    Mock::given(method("GET"))
+        .and(header("user-agent", config.user_agent.as_str()))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let body = client(config).get(&format!("{}/page", server.uri())).await.unwrap();
+    assert_eq!(body, b"ok");
+}
+
+#[tokio::test]
+async fn server_errors_are_retried() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(503))
+        .up_to_n_times(2)
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let body = client(ClientConfig::default()).get(&format!("{}/page", server.uri())).await.unwrap();
+    assert_eq!(body, b"ok");
+}
+
+#[tokio::test]
+async fn retries_run_out() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(500))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let config = ClientConfig { retries: 2, ..ClientConfig::default() };
+    let err = client(config).get(&format!("{}/page", server.uri())).await.unwrap_err();
+
+    assert!(err.to_string().contains("Failed after 3 attempts"), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn client_errors_are_not_retried() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .respond_with(ResponseTemplate::new(404))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let err = client(ClientConfig::default()).get(&format!("{}/page", server.uri())).await.unwrap_err();
+    assert!(matches!(err, WikibaseError::Network { status: Some(404), .. }), "Unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn unchanged_documents_come_from_cache() {
+    let server = MockServer::start().await;
+    let cache = temp_dir().join(format!("wikibase-http-cache-{}", std::process::id()));
+    let _ = remove_dir_all(&cache);
+
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .and(header("if-none-match", "\"v1\""))
+        .and(header_exists("if-modified-since"))
+        .respond_with(ResponseTemplate::new(304))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/page"))
+        .respond_with(ResponseTemplate::new(200)
+            .insert_header("etag", "\"v1\"")
+            .insert_header("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT")
+            .set_body_string("first"))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let client = client(ClientConfig { cache: Some(cache.clone()), ..ClientConfig::default() });
+    let url = format!("{}/page", server.uri());
+
+    assert_eq!(client.get(&url).await.unwrap(), b"first");
+    assert_eq!(client.get(&url).await.unwrap(), b"first");
+
+    let _ = remove_dir_all(&cache);
+}
+
+#[tokio::test]
+async fn requests_per_host_are_limited() {
+    let server = MockServer::start().await;
+    let delay = Duration::from_millis(100);
+
+    Mock::given(method("GET"))
+        .and(header_exists("user-agent"))
+        .respond_with(ResponseTemplate::new(200).set_delay(delay))
+        .expect(3)
+        .mount(&server)
+        .await;
+
+    let client = client(ClientConfig { per_host: 1, ..ClientConfig::default() });
+    let urls = (0..3).map(|i|format!("{}/page/{}", server.uri(), i)).collect::<Vec<String>>();
+
+    let started = Instant::now();
+    let results = join_all(urls.iter().map(|u|client.get(u))).await;
+
+    assert!(results.iter().all(|r|r.is_ok()));
+    assert!(started.elapsed() >= delay * 3, "Requests ran concurrently in {:?}", started.elapsed());
+}
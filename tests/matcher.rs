@@ -0,0 +1,63 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use common::expected;
+use wikibase::matcher::NameMatcher;
+use wikibase::types::Language;
+use wikibase::Identifier;
+
+
+fn phrases(matcher: &NameMatcher, text: &str) -> Vec<(String, String)> {
+    matcher.find(text).into_iter().map(|m|(m.phrase, m.id.to_string())).collect()
+}
+
+#[test]
+fn longest_name_wins() {
+    let mut matcher = NameMatcher::new();
+
+    matcher.insert("Haitian Creole", &Identifier::new("hat"));
+    matcher.insert("Creole", &Identifier::new("crp"));
+    matcher.insert("Southern Sotho", &Identifier::new("sot"));
+    matcher.insert("Sotho", &Identifier::new("nso"));
+
+    let text = "Haitian Creole and French; Southern Sotho, Sotho and a Creole";
+
+    assert_eq!(phrases(&matcher, text), [
+        ("Haitian Creole".to_string(), "hat".to_string()),
+        ("Southern Sotho".to_string(), "sot".to_string()),
+        ("Sotho".to_string(), "nso".to_string()),
+        ("Creole".to_string(), "crp".to_string()),
+    ]);
+
+    // Start of a longer name alone is no match
+    assert!(matcher.find("Haitian").is_empty());
+}
+
+#[test]
+fn phrases_are_found_as_written() {
+    let mut matcher = NameMatcher::new();
+    matcher.insert("Serbo-Croatian", &Identifier::new("hbs"));
+
+    let text = "De facto: serbo croatian[1]";
+    let m = &matcher.find(text)[0];
+
+    assert_eq!((m.phrase.as_str(), m.id.as_str()), ("serbo croatian", "hbs"));
+    assert_eq!(&text[m.start..m.end], m.phrase);
+}
+
+#[test]
+fn languages_are_matched_by_names_and_aliases() {
+    let languages = expected::<BTreeMap<Identifier, Language>>("languages");
+    let matcher = Language::matcher(&languages);
+
+    assert_eq!(phrases(&matcher, "Finnish language, Swedish and Northern Sami (recognized)"), [
+        ("Finnish language".to_string(), "fin".to_string()),
+        ("Swedish".to_string(), "swe".to_string()),
+        ("Northern Sami".to_string(), "sme".to_string()),
+    ]);
+    // Sami names a group of languages, not any one of them
+    assert!(matcher.find("Sami (recognized)").is_empty());
+    // Stop words and unknown names are ignored
+    assert!(matcher.find("Official languages of the autonomous republic").is_empty());
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use common::{assert_golden, countries, expected, pages};
+use serde_json::json;
+use wikibase::fetch::{Backend, Fetcher, Source};
+use wikibase::mediawiki::MediaWiki;
+use wikibase::types::{SovereignState, UNMember};
+use wikibase::urls::SOVEREIGN_STATES;
+use wiremock::matchers::{method, path, query_param, query_param_is_missing};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+
+// Article body of a synthetic page, the way the Parse API returns it
+fn article(file: &str) -> String {
+    let page = read_to_string(pages().join("en.wikipedia.org").join("wiki").join(file)).unwrap();
+    let start = page.find("<body>").map(|i|i + "<body>".len()).unwrap_or(0);
+    let end = page.rfind("</body>").unwrap_or(page.len());
+
+    format!("<div class=\"mw-parser-output\">{}</div>", &page[start..end])
+}
+
+fn parsed(revision: u64, html: String) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({
+        "parse": {
+            "title": "List of sovereign states",
+            "pageid": 27193,
+            "revid": revision,
+            "text": html,
+        }
+    }))
+}
+
+fn fetcher(server: &MockServer, api: MediaWiki) -> Fetcher {
+    Fetcher::default().with_backend(Backend::Api(api.endpoint(format!("{}/w/api.php", server.uri()))))
+}
+
+#[test]
+fn request_urls() {
+    let api = MediaWiki::new().pin(SOVEREIGN_STATES, 1251000001);
+
+    assert_eq!(
+        api.request(SOVEREIGN_STATES).unwrap(),
+        "https://en.wikipedia.org/w/api.php?action=parse&format=json&formatversion=2&prop=text%7Crevid&oldid=1251000001"
+    );
+    assert_eq!(
+        api.request("https://en.wikipedia.org/wiki/ISO_3166-1").unwrap(),
+        "https://en.wikipedia.org/w/api.php?action=parse&format=json&formatversion=2&prop=text%7Crevid&page=ISO_3166-1&redirects=1"
+    );
+
+    // Not wiki articles
+    assert_eq!(api.request("https://www.un.org/en/about-us/member-states"), None);
+    assert_eq!(api.request("https://upload.wikimedia.org/wikipedia/commons/b/bc/Flag_of_Finland.svg"), None);
+}
+
+#[tokio::test]
+async fn latest_revision_is_recorded() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/w/api.php"))
+        .and(query_param("action", "parse"))
+        .and(query_param("page", "List_of_sovereign_states"))
+        .and(query_param_is_missing("oldid"))
+        .respond_with(parsed(1251000001, article("List_of_sovereign_states.html")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let fetcher = fetcher(&server, MediaWiki::new());
+    let html = fetcher.get_html(SOVEREIGN_STATES).await.unwrap();
+
+    // Parsers read the api output the same way as the rendered page
+    let (parsed, _) = SovereignState::from_html(&html, &expected::<Vec<UNMember>>("un_nations"), &countries()).unwrap();
+    assert_golden("sovereign_states", &parsed);
+
+    assert_eq!(fetcher.sources(), vec![Source { url: SOVEREIGN_STATES.to_string(), revision: Some(1251000001) }]);
+}
+
+#[tokio::test]
+async fn pinned_revision_is_requested() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/w/api.php"))
+        .and(query_param("oldid", "1200000000"))
+        .respond_with(parsed(1200000000, article("List_of_sovereign_states.html")))
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let pins = [Source { url: SOVEREIGN_STATES.to_string(), revision: Some(1200000000) }];
+    let fetcher = fetcher(&server, MediaWiki::new().pin_sources(&pins));
+
+    fetcher.get_html(SOVEREIGN_STATES).await.unwrap();
+    assert_eq!(fetcher.sources(), pins);
+}
+
+#[tokio::test]
+async fn api_errors_are_reported() {
+    let server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/w/api.php"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "error": { "code": "nosuchrevid", "info": "There is no revision with ID 1." }
+        })))
+        .mount(&server)
+        .await;
+
+    let fetcher = fetcher(&server, MediaWiki::new().pin(SOVEREIGN_STATES, 1));
+    let err = fetcher.get_html(SOVEREIGN_STATES).await.unwrap_err().to_string();
+
+    assert!(err.contains("nosuchrevid"), "Unexpected error: {}", err);
+    assert!(fetcher.sources().is_empty());
+}
@@ -0,0 +1,234 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use scraper::Html;
+use common::{assert_golden, countries, expected, page};
+use wikibase::types::{CallingCode, Capital, Currency, Flag, Language, LanguageRegion, LanguageStatus, Region, SovereignState, UNMember};
+use wikibase::urls::*;
+use wikibase::{DatasetKind, Identifier, WikibaseError};
+
+
+fn un_member_states() -> BTreeMap<Identifier, SovereignState> {
+    expected::<BTreeMap<Identifier, SovereignState>>("sovereign_states")
+        .into_iter()
+        .filter(|(_, s)|s.un_member)
+        .collect()
+}
+
+#[tokio::test]
+async fn un_nations() {
+    let html = page(UN_NATIONS).await;
+    let parsed = UNMember::from_html(&html, &countries()).unwrap();
+
+    assert_golden("un_nations", &parsed);
+}
+
+#[tokio::test]
+async fn sovereign_states() {
+    let html = page(SOVEREIGN_STATES).await;
+    let (parsed, _) = SovereignState::from_html(&html, &expected::<Vec<UNMember>>("un_nations"), &countries()).unwrap();
+
+    assert_golden("sovereign_states", &parsed);
+}
+
+#[tokio::test]
+async fn regions() {
+    let html = page(ISO_3166).await;
+    let (parsed, _) = Region::from_html(&html, &un_member_states()).unwrap();
+
+    assert_golden("regions", &parsed);
+}
+
+#[tokio::test]
+async fn currencies() {
+    let html = page(CURRENCIES).await;
+    let (parsed, _) = Currency::from_html(&html, &expected("regions"), Some(&countries())).unwrap();
+
+    assert_golden("currencies", &parsed);
+}
+
+#[tokio::test]
+async fn emojis() {
+    let html = page(EMOJIS).await;
+    let (parsed, _) = Flag::emojis_from_html(&html, &expected("regions")).unwrap();
+
+    assert_golden("emojis", &parsed);
+}
+
+#[tokio::test]
+async fn calling_codes() {
+    let html = page(CALLING_CODES).await;
+    let (parsed, _) = CallingCode::from_html(&html, &expected("regions"), Some(&countries())).unwrap();
+
+    assert_golden("calling_codes", &parsed);
+}
+
+#[tokio::test]
+async fn languages() {
+    let html = page(LANG_CODES_ISO_639).await;
+    let (parsed, _) = Language::from_html(&html).unwrap();
+
+    assert_golden("languages", &parsed);
+}
+
+#[tokio::test]
+async fn language_zones() {
+    let html = page(LANG_ZONES).await;
+    let mut languages = expected::<BTreeMap<Identifier, Language>>("languages");
+
+    Language::zones_from_html(&html, &countries(), &expected("regions"), &mut languages).unwrap();
+
+    assert_golden("language_zones", &languages);
+}
+
+#[tokio::test]
+async fn language_statuses() {
+    let html = page(LANG_ZONES).await;
+    let mut languages = expected::<BTreeMap<Identifier, Language>>("languages");
+
+    let report = Language::zones_from_html(&html, &countries(), &expected("regions"), &mut languages).unwrap();
+
+    let (fi, se) = (Identifier::new("fi"), Identifier::new("se"));
+    let finnish = &languages[&Identifier::new("fin")];
+
+    // Finnish is co-official with Swedish in Finland, and regional in Sweden where Swedish is the only official language
+    let swedish = &languages[&Identifier::new("swe")];
+
+    assert_eq!(finnish.region(&fi).unwrap().status, Some(LanguageStatus::CoOfficial));
+    assert_eq!(finnish.region(&se).unwrap().status, Some(LanguageStatus::Regional));
+    assert_eq!(swedish.region(&fi).unwrap().status, Some(LanguageStatus::CoOfficial));
+    assert_eq!(swedish.region(&se).unwrap().status, Some(LanguageStatus::Official));
+    assert!(finnish.is_official_in(&fi) && !finnish.is_official_in(&se));
+
+    // Phrase naming each language is recorded, generic Sami is not taken for Northern Sami
+    assert!(report.matched.iter().any(|m|m.phrase == "Finnish" && m.id == Identifier::new("fin")), "{:?}", report.matched);
+    assert!(!report.matched.iter().any(|m|m.id == Identifier::new("sme")), "{:?}", report.matched);
+}
+
+#[test]
+fn languages_without_statuses_are_official() {
+    // Data sets written before statuses were recorded only have regions
+    let json = r#"{"name_short": "Swedish", "name_long": "Swedish language", "iso639": {"set1": "sv", "set2_t": "swe", "set2_b": "swe", "set3": "swe"}, "regions": ["ax", "fi", "se"]}"#;
+    let swedish = serde_json::from_str::<Language>(json).unwrap();
+
+    assert_eq!(swedish.region(&Identifier::new("fi")), Some(&LanguageRegion { region: Identifier::new("fi"), status: None }));
+    assert!(swedish.is_official_in(&Identifier::new("fi")));
+    assert!(!swedish.is_official_in(&Identifier::new("no")));
+}
+
+#[tokio::test]
+async fn capitals() {
+    let html = page(CAPITALS).await;
+    let (parsed, _) = Capital::from_html(&html, &expected("regions"), Some(&countries())).unwrap();
+
+    assert_golden("capitals", &parsed);
+}
+
+#[test]
+fn page_without_tables() {
+    let html = Html::parse_document("<html><body><p>Moved to another page</p></body></html>");
+    let err = CallingCode::from_html(&html, &expected("regions"), Some(&countries())).unwrap_err();
+
+    assert!(matches!(err, WikibaseError::Layout { dataset: DatasetKind::CallingCodes, .. }), "Unexpected error: {}", err);
+}
+
+#[test]
+fn missing_columns_are_named() {
+    // Wikipedia dropped the fraction columns
+    let html = Html::parse_document(r#"<table>
+        <tr><th>State or territory<sup>[1]</sup></th><th>Currency</th><th>Symbol</th><th>ISO code</th></tr>
+        <tr><td><a href="/wiki/Finland">Finland</a></td><td><a href="/wiki/Euro">Euro</a></td><td>€</td><td>EUR</td></tr>
+    </table>"#);
+
+    match Currency::from_html(&html, &expected("regions"), Some(&countries())).unwrap_err() {
+        WikibaseError::Layout { dataset, reason } => {
+            assert_eq!(dataset, DatasetKind::Currencies);
+            assert_eq!(reason, "Table number 0 is missing columns 'Fractional unit', 'Number to basic'");
+        },
+        e => panic!("Unexpected error: {}", e),
+    }
+}
+
+#[test]
+fn shared_calling_code_spans_rows() {
+    let html = Html::parse_document(r#"<table>
+        <tr><th>Country, territory or service</th><th>Code</th><th colspan="2">Time zone</th><th>Note</th></tr>
+        <tr><td><a href="/wiki/Finland" title="Finland">Finland</a></td><td rowspan="2"><a href="/wiki/Telephone_numbers_in_Finland">358</a></td><td colspan="2">UTC+02:00</td><td></td></tr>
+        <tr><td><a href="/wiki/%C3%85land" title="Åland">Åland</a></td><td>UTC+02:00</td><td>UTC+03:00</td><td></td></tr>
+        <tr><td colspan="5">Sweden</td></tr>
+    </table>"#);
+
+    let (parsed, report) = CallingCode::from_html(&html, &expected("regions"), Some(&countries())).unwrap();
+
+    // Row of a single cell separates groups of rows, it's not a skipped row
+    assert!(report.is_empty(), "Unexpected report: {:?}", report);
+    assert_eq!(
+        parsed,
+        BTreeMap::from([
+            (Identifier::new("ax"), CallingCode::new(358, Vec::new())),
+            (Identifier::new("fi"), CallingCode::new(358, Vec::new())),
+        ])
+    );
+}
+
+#[test]
+fn nanp_members_without_a_row_are_added() {
+    let html = Html::parse_document(r#"<table>
+        <tr><th>Country, territory or service</th><th>Code</th><th colspan="2">Time zone</th><th>Note</th></tr>
+        <tr><td><a href="/wiki/Antigua_and_Barbuda" title="Antigua and Barbuda">Antigua and Barbuda</a></td><td><a href="/wiki/Area_code_268">1 (268)</a></td><td colspan="2">UTC−04:00</td><td></td></tr>
+    </table>"#);
+
+    let (parsed, _) = CallingCode::from_html(&html, &expected("regions"), Some(&countries())).unwrap();
+
+    // Canada isn't one of the regions of the fixtures
+    assert_eq!(parsed.keys().collect::<Vec<&Identifier>>(), [&Identifier::new("ag"), &Identifier::new("us")]);
+    assert_eq!(parsed[&Identifier::new("us")], CallingCode::new(1, Vec::new()));
+}
+
+#[test]
+fn empty_country_cell_is_skipped() {
+    let html = Html::parse_document(r#"<table>
+        <tr><th>Country, territory or service</th><th>Code</th><th colspan="2">Time zone</th><th>Note</th></tr>
+        <tr><td> </td><td><a href="/wiki/Telephone_numbers_in_Finland">358</a></td><td colspan="2">UTC+02:00</td><td></td></tr>
+        <tr><td><a href="/wiki/Sweden" title="Sweden">Sweden</a></td><td><a href="/wiki/Telephone_numbers_in_Sweden">46</a></td><td colspan="2">UTC+01:00</td><td></td></tr>
+    </table>"#);
+
+    let (parsed, report) = CallingCode::from_html(&html, &expected("regions"), Some(&countries())).unwrap();
+
+    assert_eq!(parsed.keys().collect::<Vec<&Identifier>>(), [&Identifier::new("se")]);
+    assert_eq!(report.reasons(), BTreeMap::from([("No country name", 1)]));
+}
+
+#[test]
+fn country_link_without_title() {
+    let html = Html::parse_document(r#"<table>
+        <tr><th>Common and formal names</th><th>Membership within the UN System</th><th>Sovereignty dispute</th><th>Further information</th></tr>
+        <tr><td><a href="/wiki/Nowhere">Nowhere</a></td><td>UN member state</td><td>None</td><td></td></tr>
+    </table>"#);
+
+    match SovereignState::from_html(&html, &[], &countries()).unwrap_err() {
+        WikibaseError::Row { dataset, row, text, .. } => {
+            assert_eq!(dataset, DatasetKind::SovereignStates);
+            assert_eq!(row, 0);
+            assert_eq!(text, "Nowhere");
+        },
+        e => panic!("Unexpected error: {}", e),
+    }
+}
+
+#[tokio::test]
+async fn skipped_rows_are_reported() {
+    let html = page(CURRENCIES).await;
+    let (_, report) = Currency::from_html(&html, &expected("regions"), Some(&countries())).unwrap();
+
+    let skipped = report.skipped.iter()
+        .map(|s|(s.table, s.row, s.text.as_str(), s.reason.as_str()))
+        .collect::<Vec<_>>();
+
+    assert_eq!(skipped, vec![
+        (0, 0, "Åland Islands", "Country not found"),
+        (0, 4, "Faroe Islands", "Country not found"),
+        (0, 6, "Kosovo", "Country not found"),
+    ]);
+}
@@ -0,0 +1,80 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use common::expected;
+use wikibase::phone::{normalize, PrefixTrie};
+use wikibase::types::CallingCode;
+use wikibase::{Dataset, ErrorKind, Identifier};
+
+
+fn codes() -> BTreeMap<Identifier, CallingCode> {
+    expected("calling_codes")
+}
+
+#[test]
+fn numbers_are_normalized() {
+    assert_eq!(normalize("+358 40 123 4567").as_deref(), Some("358401234567"));
+    assert_eq!(normalize("00358-40-123-4567").as_deref(), Some("358401234567"));
+    assert_eq!(normalize("+1 (268) 555.0100").as_deref(), Some("12685550100"));
+    assert_eq!(normalize("46 8 123 456").as_deref(), Some("468123456"));
+
+    for invalid in ["", "+", "+358 40 abc", "+1234567890123456", "++358"] {
+        assert_eq!(normalize(invalid), None, "{} was normalized", invalid);
+    }
+}
+
+#[test]
+fn numbers_are_classified_by_longest_prefix() {
+    let trie = PrefixTrie::new(&codes());
+
+    let aland = trie.classify("+358 18 12345").unwrap();
+    assert_eq!(aland.region, Some(Identifier::new("ax")));
+    assert_eq!((aland.country_code, aland.national_number.as_str()), (358, "1812345"));
+    assert_eq!(aland.e164(), "+3581812345");
+
+    let finland = trie.classify("00358 40 123 4567").unwrap();
+    assert_eq!(finland.region, Some(Identifier::new("fi")));
+    assert_eq!(finland.national_number, "401234567");
+
+    let antigua = trie.classify("+1 268 555 0100").unwrap();
+    assert_eq!(antigua.region, Some(Identifier::new("ag")));
+    assert!(antigua.is_nanp() && !antigua.is_ambiguous());
+
+    assert_eq!(trie.classify("+999 123").unwrap_err().kind(), ErrorKind::Validation);
+    assert_eq!(trie.classify("call me").unwrap_err().kind(), ErrorKind::Validation);
+}
+
+#[test]
+fn nanp_numbers_without_an_area_code_are_ambiguous() {
+    let mut codes = codes();
+    codes.insert(Identifier::new("ca"), CallingCode::new(1, Vec::new()));
+
+    let trie = PrefixTrie::new(&codes);
+
+    // New York, in the United States as far as calling codes can tell
+    let number = trie.classify("+1 212 555 0100").unwrap();
+    assert!(number.is_ambiguous());
+    assert_eq!(number.region, None);
+    assert_eq!(number.candidates, ["ca", "us"].map(Identifier::new));
+    assert_eq!(number.national_number, "2125550100");
+
+    // Too short to tell Puerto Rico from the rest
+    let number = trie.classify("+1 78").unwrap();
+    assert_eq!(number.candidates, ["ca", "pr", "us"].map(Identifier::new));
+}
+
+#[test]
+fn committed_output_classifies_nanp_numbers() {
+    let data = Dataset::load_from_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("output")).unwrap();
+    let trie = PrefixTrie::new(&data.calling_codes);
+
+    // New York could be anywhere dialed without an area code of its own
+    let number = trie.classify("+1 212 555 0100").unwrap();
+    assert!(number.is_ambiguous());
+    assert!(number.candidates.contains(&Identifier::new("us")) && number.candidates.contains(&Identifier::new("ca")));
+
+    let number = trie.classify("+1 787 555 0100").unwrap();
+    assert_eq!(number.region, Some(Identifier::new("pr")));
+}
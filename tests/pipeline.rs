@@ -0,0 +1,217 @@
+mod common;
+
+use std::collections::BTreeSet;
+use std::env::temp_dir;
+use std::fs::{copy, create_dir_all, read_to_string, remove_dir_all, write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use common::{countries, fixtures, pages};
+use wikibase::diff::{Diff, PREVIOUS_DIR};
+use wikibase::fetch::{revision_id, Fetcher, Mode, Source};
+use wikibase::metadata::Metadata;
+use wikibase::pipeline::{Context, Output, Pipeline, Status, Summary};
+use wikibase::report::Reports;
+use wikibase::snapshot::Snapshots;
+use wikibase::urls::*;
+use wikibase::types::{Capital, Currency, Fraction, Language};
+use wikibase::{DatasetKind, Identifier};
+
+
+// Empty output directory of its own for each test
+fn output(name: &str) -> PathBuf {
+    let dir = temp_dir().join(format!("wikibase-{}-{}", name, std::process::id()));
+    let _ = remove_dir_all(&dir);
+
+    dir
+}
+
+fn context(output: &Path, selected: &[DatasetKind], max_age: Option<Duration>) -> Context {
+    Context {
+        fetcher: Fetcher::new(Mode::Replay(Snapshots::new(pages()))),
+        output: output.to_path_buf(),
+        countries: countries(),
+        selected: selected.iter().copied().collect::<BTreeSet<DatasetKind>>(),
+        force: false,
+        max_age,
+        iso_639_3: None,
+    }
+}
+
+async fn run(ctx: &Context) -> Summary {
+    let (_, summary) = Pipeline::default().run(ctx).await.unwrap();
+
+    assert!(!summary.failed(), "Pipeline failed:\n{}", summary);
+    summary
+}
+
+fn all(summary: &Summary, status: Status) -> bool {
+    summary.stages.iter().all(|s|s.status == status)
+}
+
+#[test]
+fn revision_from_page_config() {
+    let html = r#"<script>RLCONF={"wgCurRevisionId":1250400002,"wgRevisionId":1250400001};</script>"#;
+
+    assert_eq!(revision_id(html), Some(1250400001));
+    assert_eq!(revision_id("<html></html>"), None);
+    assert_eq!(revision_id(r#"{"wgRevisionId":0}"#), None);
+}
+
+#[test]
+fn snapshot_paths_are_unique() {
+    let snapshots = Snapshots::new("snapshots");
+    let path = |url: &str|snapshots.path(url).unwrap();
+
+    assert_eq!(path(SOVEREIGN_STATES), Path::new("snapshots/en.wikipedia.org/wiki/List_of_sovereign_states.html"));
+    assert_eq!(path("https://example.org/flags/fi.svg"), Path::new("snapshots/example.org/flags/fi.svg"));
+
+    let urls = [
+        "https://en.wikipedia.org/wiki/A_B",
+        "https://en.wikipedia.org/wiki/A/B",
+        "https://en.wikipedia.org/wiki/A?B",
+        "https://en.wikipedia.org/wiki/A:B",
+        "https://en.wikipedia.org/w/api.php?action=parse&page=A",
+        "https://en.wikipedia.org/w/api.php?action=parse&page=B",
+    ];
+    let paths = urls.iter().map(|u|path(u)).collect::<BTreeSet<PathBuf>>();
+
+    assert_eq!(paths.len(), urls.len(), "{:?}", paths);
+    assert!(paths.iter().all(|p|p.starts_with("snapshots/en.wikipedia.org")));
+}
+
+#[test]
+fn sets_are_normalized() {
+    let mut dollar = Currency::new("US dollar".into(), "$".into(), Fraction::new("Cent".into(), 100), Some(Identifier::new("us")));
+    dollar.regions.extend([Identifier::new("pr"), Identifier::new("ec"), Identifier::new("pr")]);
+
+    let mut currencies = Output::Currencies([(Identifier::new("usd"), dollar)].into());
+    currencies.normalize();
+
+    let Output::Currencies(c) = currencies else { unreachable!() };
+    assert_eq!(c[&Identifier::new("usd")].regions, ["ec", "pr", "us"].map(Identifier::new));
+
+    // Endonyms keep their order, the first one is the one of the main language
+    let bern = Capital::new("Bern".into(), Some(vec!["Berne".into(), "Berna".into(), "Berna".into()]));
+    let mut capitals = Output::Capitals([(Identifier::new("ch"), bern)].into());
+    capitals.normalize();
+
+    let Output::Capitals(c) = capitals else { unreachable!() };
+    assert_eq!(c[&Identifier::new("ch")].endonyms.as_deref(), Some(["Berne".to_string(), "Berna".to_string()].as_slice()));
+}
+
+#[tokio::test]
+async fn metadata_records_sources() {
+    let dir = output("sources");
+    run(&context(&dir, &[DatasetKind::Regions], None)).await;
+
+    let metadata = Metadata::read(&dir).await;
+    let regions = metadata.get(DatasetKind::Regions).unwrap();
+
+    assert_eq!(regions.sources, vec![Source { url: ISO_3166.to_string(), revision: Some(1250400002) }]);
+    assert_eq!(regions.hash.len(), 64);
+    assert!(regions.age() < Duration::from_secs(60));
+
+    // Not a wikipedia page
+    let un_nations = metadata.get(DatasetKind::UnNations).unwrap();
+    assert_eq!(un_nations.sources, vec![Source { url: UN_NATIONS.to_string(), revision: None }]);
+
+    let _ = remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn max_age_refetches_old_results() {
+    let dir = output("max-age");
+    let selected = [DatasetKind::Regions];
+
+    run(&context(&dir, &selected, None)).await;
+    let fetched = Metadata::read(&dir).await.get(DatasetKind::Regions).unwrap().fetched;
+
+    let summary = run(&context(&dir, &selected, Some(Duration::from_secs(3600)))).await;
+    assert!(all(&summary, Status::Cached), "Expected cached results:\n{}", summary);
+    assert_eq!(Metadata::read(&dir).await.get(DatasetKind::Regions).unwrap().fetched, fetched);
+
+    let summary = run(&context(&dir, &selected, Some(Duration::ZERO))).await;
+    assert!(all(&summary, Status::Built), "Expected fresh results:\n{}", summary);
+
+    let _ = remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn report_is_kept_for_cached_results() {
+    let dir = output("report");
+    let selected = [DatasetKind::Currencies];
+
+    let summary = run(&context(&dir, &selected, None)).await;
+    let skipped = |s: &Summary|s.stages.iter().find(|s|s.kind == DatasetKind::Currencies).unwrap().skipped;
+    assert_eq!(skipped(&summary), 3);
+
+    let summary = run(&context(&dir, &selected, None)).await;
+    assert!(all(&summary, Status::Cached), "Expected cached results:\n{}", summary);
+    assert_eq!(skipped(&summary), 3);
+
+    let reports = Reports::read(&dir).await;
+    assert_eq!(reports.get(DatasetKind::Currencies).unwrap().len(), 3);
+
+    let _ = remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn previous_build_is_kept_for_diff() {
+    let dir = output("previous");
+    let selected = [DatasetKind::Regions];
+    let (regions, previous) = (dir.join("regions.json"), dir.join(PREVIOUS_DIR));
+
+    run(&context(&dir, &selected, None)).await;
+    assert!(!previous.join("regions.json").exists());
+
+    // Changed by hand so the build replacing it differs from it
+    let built = read_to_string(&regions).unwrap();
+    write(&regions, built.replace("\"Finland\"", "\"Suomi\"")).unwrap();
+
+    run(&context(&dir, &selected, Some(Duration::ZERO))).await;
+    assert_eq!(read_to_string(&regions).unwrap(), built);
+
+    let diff = || Diff::read(&previous, &dir, DatasetKind::ALL).unwrap().to_string();
+    assert_eq!(diff(), "regions\n  ~ fi.name: \"Suomi\" -> \"Finland\"\n");
+
+    // Builds reading previous results leave the snapshot of the last real build alone
+    for _ in 0..2 {
+        let summary = run(&context(&dir, &selected, None)).await;
+        assert!(all(&summary, Status::Cached), "Expected cached results:\n{}", summary);
+    }
+
+    assert_eq!(diff(), "regions\n  ~ fi.name: \"Suomi\" -> \"Finland\"\n");
+
+    let _ = remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn code_tables_are_inputs_of_languages() {
+    let dir = output("iso-639-3");
+    let tables = dir.join("sil");
+    let selected = [DatasetKind::Languages];
+    let languages = |s: &Summary|s.stages.iter().find(|s|s.kind == DatasetKind::Languages).unwrap().status.clone();
+
+    create_dir_all(&tables).unwrap();
+
+    for name in Language::SIL_TABLES {
+        copy(fixtures().join("sil").join(name), tables.join(name)).unwrap();
+    }
+
+    run(&context(&dir, &selected, None)).await;
+
+    let ctx = Context { iso_639_3: Some(tables.clone()), ..context(&dir, &selected, None) };
+    assert_eq!(languages(&run(&ctx).await), Status::Built);
+    assert_eq!(languages(&run(&ctx).await), Status::Cached);
+
+    // Edited code tables are read again
+    let table = tables.join("iso-639-3-macrolanguages.tab");
+    write(&table, read_to_string(&table).unwrap().replace("cmn\tA", "cmn\tR")).unwrap();
+    assert_eq!(languages(&run(&ctx).await), Status::Built);
+
+    // As are the languages without them
+    assert_eq!(languages(&run(&context(&dir, &selected, None)).await), Status::Built);
+
+    let _ = remove_dir_all(&dir);
+}
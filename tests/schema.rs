@@ -0,0 +1,51 @@
+mod common;
+
+use std::fs::read_to_string;
+
+use common::fixtures;
+use wikibase::schema::{schema, validate};
+use wikibase::{DatasetKind, ErrorKind};
+
+
+fn golden(name: &str) -> String {
+    read_to_string(fixtures().join("expected").join(format!("{}.json", name))).unwrap()
+}
+
+#[test]
+fn parser_output_matches_schemas() {
+    let goldens = [
+        (DatasetKind::UnNations, "un_nations"),
+        (DatasetKind::SovereignStates, "sovereign_states"),
+        (DatasetKind::Regions, "regions"),
+        (DatasetKind::Emojis, "emojis"),
+        (DatasetKind::Currencies, "currencies"),
+        (DatasetKind::CallingCodes, "calling_codes"),
+        (DatasetKind::Languages, "languages"),
+        (DatasetKind::Languages, "language_zones"),
+        (DatasetKind::Capitals, "capitals"),
+    ];
+
+    for (kind, name) in goldens {
+        validate(kind, &golden(name)).unwrap_or_else(|e|panic!("{} doesn't match the schema: {}", name, e));
+    }
+}
+
+#[test]
+fn schemas_are_titled_by_data_set() {
+    for kind in DatasetKind::ALL {
+        assert_eq!(schema(kind).get("title").and_then(|t|t.as_str()), Some(kind.name()));
+    }
+}
+
+#[test]
+fn invalid_data_is_rejected() {
+    let json = golden("regions").replace("\"a2\": \"FI\"", "\"a2\": \"fin\"");
+    let err = validate(DatasetKind::Regions, &json).unwrap_err();
+
+    assert_eq!(err.kind(), ErrorKind::Validation);
+    assert!(err.to_string().contains("/fi/iso_3166_1/a2"), "Unexpected error: {}", err);
+
+    // Required fields can not be left out
+    let json = golden("currencies").replacen("\"regions\"", "\"countries\"", 1);
+    assert!(validate(DatasetKind::Currencies, &json).is_err());
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+
+use common::{expected, fixtures};
+use wikibase::types::{Language, LanguageScope, LanguageType};
+use wikibase::{ErrorKind, Identifier};
+
+
+fn languages() -> BTreeMap<Identifier, Language> {
+    expected("language_zones")
+}
+
+fn table(name: &str) -> String {
+    read_to_string(fixtures().join("sil").join(name)).unwrap()
+}
+
+#[tokio::test]
+async fn languages_without_iso_639_1_code_are_added() {
+    let mut languages = languages();
+    let report = Language::read_sil(fixtures().join("sil"), &mut languages).await.unwrap();
+
+    let mandarin = &languages[&Identifier::new("cmn")];
+    assert_eq!((mandarin.name_short.as_str(), mandarin.iso639.set1(), mandarin.iso639.set3()), ("Mandarin Chinese", "", "cmn"));
+    assert_eq!(mandarin.to_string(), "Mandarin Chinese");
+    assert_eq!(mandarin.iso639.to_string(), "cmn");
+
+    assert_eq!(languages[&Identifier::new("epo")].language_type, Some(LanguageType::Constructed));
+    assert_eq!(languages[&Identifier::new("got")].language_type, Some(LanguageType::Ancient));
+    assert_eq!(languages[&Identifier::new("mis")].scope, Some(LanguageScope::Special));
+
+    // Languages of the ISO 639-1 table keep their names and regions
+    let finnish = &languages[&Identifier::new("fin")];
+    assert_eq!((finnish.name_long.as_str(), finnish.scope, finnish.language_type), ("Finnish language", Some(LanguageScope::Individual), Some(LanguageType::Living)));
+    assert!(finnish.is_spoken_in(&Identifier::new("fi")));
+
+    let reasons = report.reasons();
+    assert_eq!(reasons.get("Unknown language scope 'X'"), Some(&1));
+    assert_eq!(reasons.get("Missing columns"), Some(&1));
+}
+
+#[test]
+fn macrolanguages_have_members() {
+    let mut languages = languages();
+    let report = Language::merge_sil(&table("iso-639-3.tab"), &table("iso-639-3-macrolanguages.tab"), &mut languages).unwrap();
+
+    let zho = Identifier::new("zho");
+    assert_eq!(languages[&zho].scope, Some(LanguageScope::Macrolanguage));
+    assert_eq!(Language::members(&languages, &zho).collect::<Vec<&Identifier>>(), [&Identifier::new("cmn"), &Identifier::new("yue")]);
+    assert_eq!(languages[&Identifier::new("yue")].macrolanguage.as_ref(), Some(&zho));
+
+    // Retired members are left out without a word, members and macrolanguages missing from the code table are reported
+    let skipped = report.skipped.iter().filter(|s|s.table == 1).map(|s|(s.text.as_str(), s.reason.as_str())).collect::<Vec<(&str, &str)>>();
+    assert_eq!(skipped, [("zho nan A", "Unknown language"), ("zza diq A", "Unknown macrolanguage")]);
+
+    // Type is written under its own name, and read back
+    let json = serde_json::to_value(&languages[&Identifier::new("yue")]).unwrap();
+    assert_eq!((json["scope"].as_str(), json["type"].as_str(), json["macrolanguage"].as_str()), (Some("individual"), Some("living"), Some("zho")));
+    assert_eq!(serde_json::from_value::<Language>(json).unwrap().language_type, Some(LanguageType::Living));
+}
+
+#[test]
+fn unknown_table_is_a_layout_error() {
+    let err = Language::merge_sil("Code\tName\nfin\tFinnish\n", &table("iso-639-3-macrolanguages.tab"), &mut languages()).unwrap_err();
+
+    assert_eq!(err.kind(), ErrorKind::Layout);
+}